@@ -0,0 +1,56 @@
+use b64::alphabet::{BCRYPT, STANDARD};
+use b64::{Base64Encoder, Engine, FastEncoder};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::io::Write;
+
+// the engines available on this CPU against the scalar engine, the encode loop of b64 1.0
+fn engines(c: &mut Criterion) {
+    let bytes = (0..1024 * 1024)
+        .map(|i: u32| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+    for engine in Engine::ALL {
+        if let Some(fast) = FastEncoder::with_engine(&STANDARD, engine) {
+            let mut out = Vec::with_capacity(bytes.len() / 3 * 4 + 4);
+            group.bench_with_input(
+                BenchmarkId::new("standard", format!("{engine:?}")),
+                &bytes,
+                |b, bytes| {
+                    b.iter(|| {
+                        out.clear();
+                        fast.encode(bytes, &mut out)
+                    })
+                },
+            );
+        }
+    }
+
+    // an alphabet the SIMD engines don't compute
+    let fast = FastEncoder::new(&BCRYPT);
+    let mut out = Vec::with_capacity(bytes.len() / 3 * 4 + 4);
+    group.bench_with_input(
+        BenchmarkId::new("bcrypt", format!("{:?}", fast.engine())),
+        &bytes,
+        |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                fast.encode(bytes, &mut out)
+            })
+        },
+    );
+
+    // the streaming encoder, wrapped as b64 -p
+    group.bench_with_input(BenchmarkId::new("Base64Encoder", "wrap 76"), &bytes, |b, bytes| {
+        b.iter(|| {
+            let mut encoder = Base64Encoder::new(Vec::with_capacity(bytes.len() / 3 * 4 * 78 / 76 + 8)).wrap(76);
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, engines);
+criterion_main!(benches);
@@ -0,0 +1,166 @@
+/*
+ * module: alphabet
+ */
+
+use crate::error::ConfigError;
+use crate::PAD_CHAR;
+use std::str::FromStr;
+
+// Base64 alphabet: "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+#[rustfmt::skip]
+const B64TABLE: [u8; 64] = [
+     65,  66,  67,  68,  69,  70,  71,  72,  73,  74,  75,  76,  77,  // "ABCDEFGHIJKLM"
+     78,  79,  80,  81,  82,  83,  84,  85,  86,  87,  88,  89,  90,  // "NOPQRSTUVWXYZ"
+     97,  98,  99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109,  // "abcdefghijklm"
+    110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122,  // "nopqrstuvwxyz"
+     48,  49,  50,  51,  52,  53,  54,  55,  56,  57,                 // "0123456789"
+     43,                                                              // "+"
+     47                                                               // "/"
+];
+
+// Reverse index that yields the 6 bit value (position in the alphabet) of every byte,
+// INVALID for bytes which are not in the alphabet
+pub(crate) const INVALID: u8 = 0xff;
+
+const fn reverse_table(table: &[u8; 64]) -> [u8; 256] {
+    let mut reverse = [INVALID; 256];
+    let mut i = 0;
+    while i < table.len() {
+        reverse[table[i] as usize] = i as u8;
+        i += 1;
+    }
+    reverse
+}
+
+// An Alphabet is the 64 chars of a Base64 variant, in the order of their 6 bit values,
+// and the reverse index of the values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    chars: [u8; 64],
+    values: [u8; 256],
+}
+
+// RFC 4648 §4
+pub const STANDARD: Alphabet = Alphabet::from_table(&B64TABLE);
+// RFC 4648 §5, URL and filename safe
+pub const URL_SAFE: Alphabet =
+    Alphabet::from_table(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_");
+// OpenBSD bcrypt
+pub const BCRYPT: Alphabet = Alphabet::from_table(b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789");
+// crypt(3), the alphabet only, crypt(3) hashes encode the bits lsb first
+pub const CRYPT: Alphabet = Alphabet::from_table(b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz");
+
+impl Alphabet {
+    const fn from_table(chars: &[u8; 64]) -> Self {
+        Self {
+            chars: *chars,
+            values: reverse_table(chars),
+        }
+    }
+
+    // a custom alphabet of 64 distinct printable ASCII chars, '=' is reserved for padding
+    pub fn new(chars: &[u8]) -> Result<Self, ConfigError> {
+        let invalid =
+            |reason: &str| ConfigError::InvalidAlphabet(format!("{:?} {reason}", String::from_utf8_lossy(chars)));
+        let table: &[u8; 64] = chars
+            .try_into()
+            .map_err(|_| invalid(&format!("has {} chars, not 64", chars.len())))?;
+        if let Some(&ch) = table.iter().find(|&&ch| !ch.is_ascii_graphic() || ch == PAD_CHAR) {
+            return Err(invalid(&format!("has the invalid char {:?}", char::from(ch))));
+        }
+
+        // a repeated char has the value of its last position
+        let alphabet = Self::from_table(table);
+        match table
+            .iter()
+            .enumerate()
+            .find(|&(i, &ch)| alphabet.values[ch as usize] != i as u8)
+        {
+            Some((_, &ch)) => Err(invalid(&format!("repeats {:?}", char::from(ch)))),
+            None => Ok(alphabet),
+        }
+    }
+
+    pub fn chars(&self) -> &[u8; 64] {
+        &self.chars
+    }
+
+    // the char of a 6 bit value
+    #[inline]
+    pub(crate) fn char(&self, value: u8) -> u8 {
+        self.chars[value as usize]
+    }
+
+    // the 6 bit value of a char, INVALID when not in the alphabet
+    #[inline]
+    pub(crate) fn value(&self, ch: u8) -> u8 {
+        self.values[ch as usize]
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        STANDARD
+    }
+}
+
+//================================================
+// Variant is an alphabet and padding policy
+//    Standard -- RFC 4648 §4 "+/", padded
+//    Url      -- RFC 4648 §5 "-_", padded,
+//                JWTs are unpadded (pad(false))
+//    Mime     -- RFC 2045, the standard alphabet,
+//                padded, lines of 76 chars ending
+//                in CRLF
+//    Bcrypt   -- "./A-Za-z0-9", unpadded
+//    Crypt    -- "./0-9A-Za-z", unpadded, bits are
+//                msb first as in Base64, not the
+//                lsb first order of crypt(3) hashes
+//================================================
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    Url,
+    Mime,
+    Bcrypt,
+    Crypt,
+}
+
+pub const VARIANT_NAMES: [&str; 5] = ["standard", "url", "mime", "bcrypt", "crypt"];
+
+// MIME line length (RFC 2045 §6.8)
+pub const MIME_COLUMNS: usize = 76;
+
+impl Variant {
+    pub fn alphabet(self) -> Alphabet {
+        match self {
+            Self::Standard | Self::Mime => STANDARD,
+            Self::Url => URL_SAFE,
+            Self::Bcrypt => BCRYPT,
+            Self::Crypt => CRYPT,
+        }
+    }
+
+    // whether the encoded output ends with '=' padding
+    pub fn padded(self) -> bool {
+        !matches!(self, Self::Bcrypt | Self::Crypt)
+    }
+}
+
+impl FromStr for Variant {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "url" => Ok(Self::Url),
+            "mime" => Ok(Self::Mime),
+            "bcrypt" => Ok(Self::Bcrypt),
+            "crypt" => Ok(Self::Crypt),
+            _ => Err(ConfigError::InvalidVariant(format!(
+                "{s:?}, use one of {VARIANT_NAMES:?}"
+            ))),
+        }
+    }
+}
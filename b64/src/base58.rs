@@ -0,0 +1,97 @@
+/*
+ * module: base58
+ */
+
+use crate::alphabet::INVALID;
+use crate::codec::Codec;
+use crate::error::DecodeError;
+
+// Bitcoin alphabet, without "0OIl"
+const B58CHARS: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Base58 encodes the input as one big number, it is not a block codec: the input is
+// buffered until the end, and encoding/decoding is quadratic in its size
+//
+// a leading zero byte is a leading '1'
+#[derive(Clone, Debug)]
+pub struct Base58 {
+    values: [u8; 256],
+    // encode: the input bytes, decode: the input values
+    input: Vec<u8>,
+    offset: usize,
+}
+
+impl Base58 {
+    pub fn new() -> Self {
+        let mut values = [INVALID; 256];
+        for (i, &ch) in B58CHARS.iter().enumerate() {
+            values[ch as usize] = i as u8;
+        }
+        Self {
+            values,
+            input: vec![],
+            offset: 0,
+        }
+    }
+}
+
+impl Default for Base58 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the little endian digits in radix `to` of the big endian digits in radix `from`
+fn convert(digits: &[u8], from: u32, to: u32) -> Vec<u8> {
+    let mut converted: Vec<u8> = vec![];
+    for &digit in digits {
+        let mut carry = digit as u32;
+        for d in converted.iter_mut() {
+            carry += *d as u32 * from;
+            *d = (carry % to) as u8;
+            carry /= to;
+        }
+        while carry > 0 {
+            converted.push((carry % to) as u8);
+            carry /= to;
+        }
+    }
+    converted
+}
+
+impl Codec for Base58 {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        self.input.extend_from_slice(input);
+        if last {
+            let zeros = self.input.iter().take_while(|&&b| b == 0).count();
+            out.resize(out.len() + zeros, B58CHARS[0]);
+            let digits = convert(&self.input[zeros..], 256, 58);
+            out.extend(digits.iter().rev().map(|&d| B58CHARS[d as usize]));
+            self.input.clear();
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for (i, &ch) in input.iter().enumerate() {
+            match self.values[ch as usize] {
+                _ if ch.is_ascii_whitespace() => {}
+                INVALID => {
+                    return Err(DecodeError::InvalidCharacter {
+                        offset: self.offset + i,
+                        byte: ch,
+                    })
+                }
+                value => self.input.push(value),
+            }
+        }
+        self.offset += input.len();
+        if last {
+            let zeros = self.input.iter().take_while(|&&v| v == 0).count();
+            out.resize(out.len() + zeros, 0);
+            let bytes = convert(&self.input[zeros..], 58, 256);
+            out.extend(bytes.iter().rev());
+            self.input.clear();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+/*
+ * module: base64
+ */
+
+use crate::alphabet::{Alphabet, Variant, INVALID, STANDARD};
+use crate::codec::Codec;
+use crate::error::DecodeError;
+use crate::fast::FastEncoder;
+use crate::{decode_group, encode_group, PAD_CHAR};
+
+// longest PEM armor line
+const ARMOR_LINE: usize = 256;
+
+// Base64 is the Codec of Base64Encoder, Base64Decoder, and CodecType::Base64
+//
+// whole groups are encoded by the fastest engine of the CPU, decoding ignores whitespace
+// (' ', '\t', '\n', '\r', '\x0c') and skips PEM armor lines, "-----BEGIN X-----" and
+// "-----END X-----", the data of each BEGIN/END block is one stream
+pub struct Base64 {
+    alphabet: Alphabet,
+    pad: bool,
+    lenient: bool,
+    ignore_garbage: bool,
+    fast: FastEncoder,
+
+    // encode: bytes of an incomplete group, carried to the next call
+    pending: [u8; 3],
+    npending: usize,
+    // decode: chars of an incomplete group and their input offsets, and the input offset
+    group: [u8; 4],
+    offsets: [usize; 4],
+    ngroup: usize,
+    offset: usize,
+    // a padded group ends the data
+    padded: bool,
+    // at the start of a line, and the PEM armor line being read with its offset
+    line_start: bool,
+    armor: Option<(Vec<u8>, usize)>,
+}
+
+impl Default for Base64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Base64 {
+    // RFC 4648 §4, padded
+    pub fn new() -> Self {
+        Self {
+            alphabet: STANDARD,
+            pad: true,
+            lenient: false,
+            ignore_garbage: false,
+            fast: FastEncoder::new(&STANDARD),
+            pending: [0; 3],
+            npending: 0,
+            group: [0; 4],
+            offsets: [0; 4],
+            ngroup: 0,
+            offset: 0,
+            padded: false,
+            line_start: true,
+            armor: None,
+        }
+    }
+
+    // the alphabet and padding of `variant`
+    pub fn variant(self, variant: Variant) -> Self {
+        self.alphabet(variant.alphabet()).pad(variant.padded())
+    }
+
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.fast = FastEncoder::new(&alphabet);
+        self.alphabet = alphabet;
+        self
+    }
+
+    // pad the final group with '=' to 4 chars when encoding, when decoding require the final
+    // group to be padded, unpadded input ends with a group of 2 or 3 chars
+    // (padding is accepted either way)
+    pub fn pad(mut self, pad: bool) -> Self {
+        self.pad = pad;
+        self
+    }
+
+    // skip the data integrity checks: non-zero trailing bits, data after padding,
+    // and a final group without padding are decoded, a final single char is dropped
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    // skip bytes which are not in the alphabet (as GNU base64 -i)
+    pub fn ignore_garbage(mut self, ignore_garbage: bool) -> Self {
+        self.ignore_garbage = ignore_garbage;
+        self
+    }
+
+    // a complete PEM armor line, BEGIN starts a stream and END finishes it
+    // a line which is not armor is an invalid character at its '-', or garbage
+    fn armor_line(&mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let (line, offset) = self.armor.take().expect("an armor line");
+        self.line_start = true;
+        let line = line.trim_ascii_end();
+        let armor = line.ends_with(b"-----");
+        match line {
+            _ if armor && line.starts_with(b"-----BEGIN ") => match self.ngroup {
+                0 => {
+                    self.padded = false;
+                    Ok(())
+                }
+                len => Err(DecodeError::IncompleteGroup {
+                    offset: self.offsets[0],
+                    len,
+                }),
+            },
+            _ if armor && line.starts_with(b"-----END ") => {
+                self.finish_group(out)?;
+                self.ngroup = 0;
+                Ok(())
+            }
+            _ if self.ignore_garbage => Ok(()),
+            _ => Err(DecodeError::InvalidCharacter { offset, byte: b'-' }),
+        }
+    }
+
+    // the end of the input, a final group without padding is an error unless unpadded or lenient
+    fn finish_group(&mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match (self.ngroup, self.lenient, self.pad) {
+            (0, _, _) | (1, true, _) => Ok(()),
+            (len @ 2..=3, _, false) | (len, true, _) => {
+                let mut dst = [0; 3];
+                self.group[len..].fill(PAD_CHAR);
+                self.offsets[len..].fill(self.offset);
+                let nbytes = decode_group(self.group, &mut dst, self.lenient, &self.alphabet)
+                    .map_err(|e| e.at(&self.offsets))?;
+                out.extend_from_slice(&dst[..nbytes]);
+                Ok(())
+            }
+            (len, _, _) => Err(DecodeError::IncompleteGroup {
+                offset: self.offsets[0],
+                len,
+            }),
+        }
+    }
+}
+
+impl Codec for Base64 {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        let mut src = input;
+        let mut dst = [0; 4];
+
+        // complete a pending group
+        if self.npending > 0 {
+            let n = (3 - self.npending).min(src.len());
+            self.pending[self.npending..self.npending + n].copy_from_slice(&src[..n]);
+            self.npending += n;
+            src = &src[n..];
+            if self.npending == 3 {
+                encode_group(self.pending, &mut dst, 3, &self.alphabet);
+                out.extend_from_slice(&dst);
+                self.npending = 0;
+            }
+        }
+
+        // encode the whole groups, keep the remainder for the next call
+        if self.npending == 0 {
+            let whole = self.fast.encode(src, out);
+            let rest = &src[whole..];
+            self.pending[..rest.len()].copy_from_slice(rest);
+            self.npending = rest.len();
+        }
+
+        if last && self.npending > 0 {
+            encode_group(self.pending, &mut dst, self.npending, &self.alphabet);
+            let len = match self.pad {
+                true => 4,
+                false => self.npending + 1,
+            };
+            out.extend_from_slice(&dst[..len]);
+            self.npending = 0;
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let mut dst = [0; 3];
+        for (i, &ch) in input.iter().enumerate() {
+            let offset = self.offset + i;
+
+            if let Some((line, _)) = self.armor.as_mut() {
+                match (ch, line.len() < ARMOR_LINE) {
+                    (b'\n', _) => self.armor_line(out)?,
+                    (_, true) => line.push(ch),
+                    (_, false) => {}
+                }
+                continue;
+            }
+
+            // formatted Base64 allows for embedded whitespace that is ignored
+            if ch.is_ascii_whitespace() {
+                self.line_start |= ch == b'\n';
+                continue;
+            }
+
+            // a PEM armor line begins with '-', which is not in the standard alphabet
+            if self.line_start && ch == b'-' && self.alphabet.value(ch) == INVALID {
+                self.armor = Some((vec![ch], offset));
+                continue;
+            }
+            self.line_start = false;
+            if ch != PAD_CHAR && self.alphabet.value(ch) == INVALID {
+                match self.ignore_garbage {
+                    true => continue,
+                    false => return Err(DecodeError::InvalidCharacter { offset, byte: ch }),
+                }
+            }
+            if self.padded {
+                match self.lenient {
+                    // concatenated Base64
+                    true => self.padded = false,
+                    false => return Err(DecodeError::InvalidPadding(offset)),
+                }
+            }
+
+            // '=' only pads the third and fourth chars of a group (lenient drops a final one)
+            if ch == PAD_CHAR && self.ngroup < 2 && !self.lenient {
+                return Err(DecodeError::InvalidPadding(offset));
+            }
+
+            self.group[self.ngroup] = ch;
+            self.offsets[self.ngroup] = offset;
+            self.ngroup += 1;
+            if self.ngroup == 4 {
+                let nbytes = decode_group(self.group, &mut dst, self.lenient, &self.alphabet)
+                    .map_err(|e| e.at(&self.offsets))?;
+                out.extend_from_slice(&dst[..nbytes]);
+                self.padded = nbytes < 3;
+                self.ngroup = 0;
+            }
+        }
+        self.offset += input.len();
+
+        if !last {
+            return Ok(());
+        }
+        if self.armor.is_some() {
+            self.armor_line(out)?;
+        }
+        self.finish_group(out)
+    }
+}
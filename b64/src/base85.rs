@@ -0,0 +1,142 @@
+/*
+ * module: base85
+ */
+
+use crate::alphabet::INVALID;
+use crate::codec::Codec;
+use crate::error::DecodeError;
+
+// ZeroMQ RFC 32
+const Z85CHARS: &[u8; 85] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+// Base85 encodes a block of 4 bytes, a big endian u32, as 5 radix 85 chars,
+// a final block of 1 to 3 bytes is padded with zero bytes and encoded as 2 to 4 chars
+//
+// Ascii85 chars are '!' to 'u', an all zero block is 'z', Z85 has no abbreviation
+// (ZeroMQ RFC 32 requires blocks of 4 bytes, a final incomplete block is an extension
+// encoded as in Ascii85, e.g. "abc" is "vpAZ", documented in b64 --help)
+#[derive(Clone, Debug)]
+pub struct Base85 {
+    chars: [u8; 85],
+    values: [u8; 256],
+    zero: Option<u8>,
+
+    // bytes or chars of the current block
+    block: [u8; 5],
+    n: usize,
+    // decode: input offset and the offset of the current block
+    offset: usize,
+    start: usize,
+}
+
+impl Base85 {
+    fn new(chars: [u8; 85], zero: Option<u8>) -> Self {
+        let mut values = [INVALID; 256];
+        for (i, &ch) in chars.iter().enumerate() {
+            values[ch as usize] = i as u8;
+        }
+        Self {
+            chars,
+            values,
+            zero,
+            block: [0; 5],
+            n: 0,
+            offset: 0,
+            start: 0,
+        }
+    }
+
+    // btoa/Adobe Ascii85, without the <~ ~> delimiters
+    pub fn ascii85() -> Self {
+        let mut chars = [0; 85];
+        for (i, ch) in (b'!'..=b'u').enumerate() {
+            chars[i] = ch;
+        }
+        Self::new(chars, Some(b'z'))
+    }
+
+    pub fn z85() -> Self {
+        Self::new(*Z85CHARS, None)
+    }
+
+    // the chars of a block of 4 bytes
+    fn encode_block(&self, bytes: [u8; 4]) -> [u8; 5] {
+        let mut value = u32::from_be_bytes(bytes);
+        let mut chars = [0; 5];
+        for ch in chars.iter_mut().rev() {
+            *ch = self.chars[(value % 85) as usize];
+            value /= 85;
+        }
+        chars
+    }
+
+    // the bytes of a block of 5 values, a value over u32::MAX is an error at the block offset
+    fn decode_block(&self, values: [u8; 5]) -> Result<[u8; 4], DecodeError> {
+        let value = values.iter().fold(0u64, |acc, &v| acc * 85 + v as u64);
+        match u32::try_from(value) {
+            Ok(value) => Ok(value.to_be_bytes()),
+            Err(_) => Err(DecodeError::Overflow(self.start)),
+        }
+    }
+}
+
+impl Codec for Base85 {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        for &byte in input {
+            self.block[self.n] = byte;
+            self.n += 1;
+            if self.n == 4 {
+                let bytes = [self.block[0], self.block[1], self.block[2], self.block[3]];
+                match (self.zero, bytes) {
+                    (Some(zero), [0, 0, 0, 0]) => out.push(zero),
+                    _ => out.extend_from_slice(&self.encode_block(bytes)),
+                }
+                self.n = 0;
+            }
+        }
+        if last && self.n > 0 {
+            let mut bytes = [0; 4];
+            bytes[..self.n].copy_from_slice(&self.block[..self.n]);
+            out.extend_from_slice(&self.encode_block(bytes)[..self.n + 1]);
+            self.n = 0;
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for (i, &ch) in input.iter().enumerate() {
+            let offset = self.offset + i;
+            match self.values[ch as usize] {
+                _ if ch.is_ascii_whitespace() => {}
+                _ if Some(ch) == self.zero && self.n == 0 => out.extend_from_slice(&[0; 4]),
+                INVALID => return Err(DecodeError::InvalidCharacter { offset, byte: ch }),
+                value => {
+                    if self.n == 0 {
+                        self.start = offset;
+                    }
+                    self.block[self.n] = value;
+                    self.n += 1;
+                    if self.n == 5 {
+                        out.extend_from_slice(&self.decode_block(self.block)?);
+                        self.n = 0;
+                    }
+                }
+            }
+        }
+        self.offset += input.len();
+
+        // a final block of 2 to 4 chars is padded with the highest value ('u' in Ascii85)
+        match (last, self.n) {
+            (false, _) | (true, 0) => Ok(()),
+            (true, 1) => Err(DecodeError::IncompleteGroup {
+                offset: self.start,
+                len: 1,
+            }),
+            (true, n) => {
+                self.block[n..].fill(84);
+                out.extend_from_slice(&self.decode_block(self.block)?[..n - 1]);
+                self.n = 0;
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+/*
+ * module: bits
+ */
+
+use crate::alphabet::INVALID;
+use crate::codec::Codec;
+use crate::error::DecodeError;
+use crate::PAD_CHAR;
+
+// a char which is skipped when decoding, e.g. a newline
+const IGNORE: u8 = 0xfe;
+const WHITESPACE: &[u8] = b" \t\r\n\x0c";
+
+// BitCodec packs the bits of the input into chars of `bits` bits, the radix 2^bits codecs:
+// hex (4 bits) and Base32 (5 bits), Base64 is the Base64 codec
+//
+// a block is the least number of chars holding whole bytes, 2 hex digits or
+// 8 Base32 chars (5 bytes), a final incomplete block
+// is padded with '=' to a whole block when padding, whitespace is ignored when decoding
+#[derive(Clone, Debug)]
+pub struct BitCodec {
+    bits: u32,
+    chars: &'static [u8],
+    // the value of each char, INVALID or IGNORE
+    values: [u8; 256],
+    pad: bool,
+
+    // bits not yet encoded/decoded, the low `nbits` of `acc`
+    acc: u32,
+    nbits: u32,
+    // chars of the current block
+    nchars: usize,
+    // decode: input offset, offsets of the current block and of its last char,
+    // and the number of pad chars which end the block
+    offset: usize,
+    start: usize,
+    last: usize,
+    npad: usize,
+    ended: bool,
+}
+
+impl BitCodec {
+    fn new(bits: u32, chars: &'static [u8], ignore: &[u8]) -> Self {
+        let mut values = [INVALID; 256];
+        for (i, &ch) in chars.iter().enumerate() {
+            values[ch as usize] = i as u8;
+        }
+        for &ch in ignore {
+            values[ch as usize] = IGNORE;
+        }
+        Self {
+            bits,
+            chars,
+            values,
+            pad: false,
+            acc: 0,
+            nbits: 0,
+            nchars: 0,
+            offset: 0,
+            start: 0,
+            last: 0,
+            npad: 0,
+            ended: false,
+        }
+    }
+
+    // RFC 4648 §6
+    pub fn base32() -> Self {
+        Self::new(5, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567", WHITESPACE).pad(true)
+    }
+
+    // Crockford's Base32, decoding is case insensitive, 'I' 'L' are '1', 'O' is '0',
+    // and '-' is ignored
+    pub fn crockford() -> Self {
+        let mut codec = Self::new(5, b"0123456789ABCDEFGHJKMNPQRSTVWXYZ", b" \t\r\n\x0c-").fold_case();
+        for (alias, ch) in [(b'I', b'1'), (b'L', b'1'), (b'O', b'0')] {
+            codec.values[alias as usize] = codec.values[ch as usize];
+            codec.values[alias.to_ascii_lowercase() as usize] = codec.values[ch as usize];
+        }
+        codec
+    }
+
+    // lowercase hex digits, decoding is case insensitive (xxd -p -r)
+    pub fn hex() -> Self {
+        Self::new(4, b"0123456789abcdef", WHITESPACE).fold_case()
+    }
+
+    // pad the final block with '=' when encoding, require padding when decoding
+    pub fn pad(mut self, pad: bool) -> Self {
+        self.pad = pad;
+        self
+    }
+
+    // decode either case of the letters
+    fn fold_case(mut self) -> Self {
+        for &ch in self.chars {
+            self.values[ch.to_ascii_lowercase() as usize] = self.values[ch as usize];
+            self.values[ch.to_ascii_uppercase() as usize] = self.values[ch as usize];
+        }
+        self
+    }
+
+    // chars of a whole block, 8 bits * chars == bits * chars
+    fn block(&self) -> usize {
+        match self.bits {
+            4 => 2,
+            _ => 8,
+        }
+    }
+
+    fn mask(nbits: u32) -> u32 {
+        (1 << nbits) - 1
+    }
+
+    // the end of the data, at padding or the end of the input
+    // the final chars must hold whole bytes, with the unused trailing bits zero
+    fn end(&mut self) -> Result<(), DecodeError> {
+        if self.nbits >= self.bits {
+            return Err(DecodeError::IncompleteGroup {
+                offset: self.start,
+                len: self.nchars,
+            });
+        }
+        match self.acc & Self::mask(self.nbits) {
+            0 => Ok(()),
+            _ => Err(DecodeError::NonCanonical(self.last)),
+        }
+    }
+}
+
+impl Codec for BitCodec {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        for &byte in input {
+            self.acc = (self.acc << 8 | byte as u32) & Self::mask(self.nbits + 8);
+            self.nbits += 8;
+            while self.nbits >= self.bits {
+                self.nbits -= self.bits;
+                out.push(self.chars[(self.acc >> self.nbits & Self::mask(self.bits)) as usize]);
+                self.nchars += 1;
+            }
+        }
+        if last {
+            if self.nbits > 0 {
+                out.push(self.chars[(self.acc << (self.bits - self.nbits) & Self::mask(self.bits)) as usize]);
+                self.nchars += 1;
+                self.nbits = 0;
+            }
+            let block = self.block();
+            self.nchars %= block;
+            if self.pad && self.nchars > 0 {
+                out.resize(out.len() + block - self.nchars, PAD_CHAR);
+            }
+            self.nchars = 0;
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for (i, &ch) in input.iter().enumerate() {
+            let offset = self.offset + i;
+            let value = self.values[ch as usize];
+            if value == IGNORE {
+                continue;
+            }
+            if self.ended || (self.npad > 0 && ch != PAD_CHAR) {
+                return Err(DecodeError::InvalidPadding(offset));
+            }
+            match (ch, value) {
+                (PAD_CHAR, _) => {
+                    if self.npad == 0 {
+                        // padding ends a block of data chars which hold whole bytes
+                        match (self.nchars, self.end()) {
+                            (0, _) | (_, Err(DecodeError::IncompleteGroup { .. })) => {
+                                return Err(DecodeError::InvalidPadding(offset))
+                            }
+                            (_, result) => result?,
+                        }
+                    }
+                    self.npad += 1;
+                    self.ended = self.nchars + self.npad == self.block();
+                }
+                (_, INVALID) => return Err(DecodeError::InvalidCharacter { offset, byte: ch }),
+                _ => {
+                    if self.nchars == 0 {
+                        self.start = offset;
+                    }
+                    self.acc = (self.acc << self.bits | value as u32) & Self::mask(self.nbits + self.bits);
+                    self.nbits += self.bits;
+                    self.last = offset;
+                    if self.nbits >= 8 {
+                        self.nbits -= 8;
+                        out.push((self.acc >> self.nbits) as u8);
+                    }
+                    self.nchars = (self.nchars + 1) % self.block();
+                }
+            }
+        }
+        self.offset += input.len();
+
+        if !last {
+            return Ok(());
+        }
+        match (self.nchars, self.npad, self.ended) {
+            (0, _, _) | (_, _, true) => Ok(()),
+            // a final block without padding, or with padding short of a whole block
+            (_, 0, false) if !self.pad => self.end(),
+            (len, npad, false) => Err(DecodeError::IncompleteGroup {
+                offset: self.start,
+                len: len + npad,
+            }),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+/*
+ * module: codec
+ */
+
+use crate::base58::Base58;
+use crate::base64::Base64;
+use crate::base85::Base85;
+use crate::bits::BitCodec;
+use crate::error::{ConfigError, DecodeError};
+use crate::xxd::Xxd;
+use crate::CHUNK_SIZE;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+// Codec is a streaming text encoding of bytes, a codec value encodes or decodes one stream,
+// carrying an incomplete block from one call to the next
+pub trait Codec {
+    // append the encoding of `input` to `out`, `last` is the end of the input
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>);
+
+    // append the bytes of the encoded `input` to `out`, `last` is the end of the input,
+    // error offsets count from the start of the stream
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError>;
+}
+
+impl<C: Codec + ?Sized> Codec for Box<C> {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        (**self).encode(input, last, out)
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        (**self).decode(input, last, out)
+    }
+}
+
+//================================================
+// CodecType names the codecs
+//    Base64    -- RFC 4648 §4
+//    Base32    -- RFC 4648 §6, padded
+//    Crockford -- Crockford's Base32, unpadded,
+//                 decoding is case insensitive
+//    Base58    -- the Bitcoin alphabet
+//    Ascii85   -- btoa/Adobe without <~ ~>
+//    Z85       -- ZeroMQ RFC 32
+//    Hex       -- lowercase hex digits (xxd -p)
+//    Xxd       -- an xxd hex dump
+//================================================
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CodecType {
+    #[default]
+    Base64,
+    Base32,
+    Crockford,
+    Base58,
+    Ascii85,
+    Z85,
+    Hex,
+    Xxd,
+}
+
+pub const CODEC_NAMES: [&str; 8] = [
+    "base64",
+    "base32",
+    "crockford",
+    "base58",
+    "ascii85",
+    "z85",
+    "hex",
+    "xxd",
+];
+
+impl CodecType {
+    // a codec for one stream, `pad` is the padding of Base64 and Base32
+    pub fn codec(self, pad: bool) -> Box<dyn Codec> {
+        match self {
+            Self::Base64 => Box::new(Base64::new().pad(pad)),
+            Self::Base32 => Box::new(BitCodec::base32().pad(pad)),
+            Self::Crockford => Box::new(BitCodec::crockford()),
+            Self::Base58 => Box::new(Base58::new()),
+            Self::Ascii85 => Box::new(Base85::ascii85()),
+            Self::Z85 => Box::new(Base85::z85()),
+            Self::Hex => Box::new(BitCodec::hex()),
+            Self::Xxd => Box::new(Xxd::new()),
+        }
+    }
+}
+
+impl FromStr for CodecType {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base64" => Ok(Self::Base64),
+            "base32" => Ok(Self::Base32),
+            "crockford" => Ok(Self::Crockford),
+            "base58" => Ok(Self::Base58),
+            "ascii85" => Ok(Self::Ascii85),
+            "z85" => Ok(Self::Z85),
+            "hex" => Ok(Self::Hex),
+            "xxd" => Ok(Self::Xxd),
+            _ => Err(ConfigError::InvalidCodec(format!("{s:?}, use one of {CODEC_NAMES:?}"))),
+        }
+    }
+}
+
+// Encoder is a Write adapter over any Codec, Base64Encoder is an Encoder of Base64
+//
+// let mut encoder = Encoder::new(io::stdout(), CodecType::Base32.codec(true)).wrap(76);
+// io::copy(&mut file, &mut encoder)?;
+// encoder.finish()?;
+pub struct Encoder<W: Write, C: Codec> {
+    inner: Option<W>,
+    codec: C,
+    // output of the codec, and the wrapped output not yet written to `inner`
+    encoded: Vec<u8>,
+    out: Vec<u8>,
+    // insert a newline every `wrap` chars, 0 for none
+    wrap: usize,
+    column: usize,
+    crlf: bool,
+}
+
+impl<W: Write, C: Codec> Encoder<W, C> {
+    pub fn new(inner: W, codec: C) -> Self {
+        Self {
+            inner: Some(inner),
+            codec,
+            encoded: Vec::with_capacity(CHUNK_SIZE),
+            out: Vec::with_capacity(CHUNK_SIZE + CHUNK_SIZE / 2),
+            wrap: 0,
+            column: 0,
+            crlf: false,
+        }
+    }
+
+    // break the output into lines of `columns` chars, there is no final newline
+    pub fn wrap(mut self, columns: usize) -> Self {
+        self.wrap = columns;
+        self
+    }
+
+    // end the wrapped lines with "\r\n" rather than "\n"
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.crlf = crlf;
+        self
+    }
+
+    pub(crate) fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    // move the codec output to `out`, a newline is inserted before a char past the wrap column
+    fn push(&mut self) {
+        if self.wrap == 0 {
+            self.out.append(&mut self.encoded);
+            return;
+        }
+        let mut chars = &self.encoded[..];
+        while !chars.is_empty() {
+            if self.column == self.wrap {
+                if self.crlf {
+                    self.out.push(b'\r');
+                }
+                self.out.push(b'\n');
+                self.column = 0;
+            }
+            let n = (self.wrap - self.column).min(chars.len());
+            self.out.extend_from_slice(&chars[..n]);
+            self.column += n;
+            chars = &chars[n..];
+        }
+        self.encoded.clear();
+    }
+
+    fn write_out(&mut self) -> io::Result<()> {
+        if !self.out.is_empty() {
+            self.inner.as_mut().expect("not finished").write_all(&self.out)?;
+            self.out.clear();
+        }
+        Ok(())
+    }
+
+    // encode the final block, flush, and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finish_mut()?;
+        Ok(self.inner.take().expect("not finished"))
+    }
+
+    fn finish_mut(&mut self) -> io::Result<()> {
+        self.codec.encode(&[], true, &mut self.encoded);
+        self.push();
+        self.write_out()?;
+        self.inner.as_mut().expect("not finished").flush()
+    }
+}
+
+impl<W: Write, C: Codec> Write for Encoder<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for chunk in buf.chunks(CHUNK_SIZE / 2) {
+            self.codec.encode(chunk, false, &mut self.encoded);
+            self.push();
+            if self.out.len() >= CHUNK_SIZE {
+                self.write_out()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    // flushes the encoded output, an incomplete block waits for more input or finish()
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_out()?;
+        self.inner.as_mut().expect("not finished").flush()
+    }
+}
+
+// an unfinished encoder is finished on drop, errors are ignored (as in BufWriter)
+impl<W: Write, C: Codec> Drop for Encoder<W, C> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.finish_mut();
+        }
+    }
+}
+
+// Decoder is a Read adapter over any Codec, Base64Decoder is a Decoder of Base64
+//
+// let mut decoder = Decoder::new(file, CodecType::Hex.codec(true));
+// io::copy(&mut decoder, &mut io::stdout())?;
+//
+// invalid input is an ErrorKind::InvalidData io::Error wrapping a DecodeError
+pub struct Decoder<R: Read, C: Codec> {
+    inner: R,
+    codec: C,
+    input: Vec<u8>,
+    // decoded output not yet read, from `pos`
+    out: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read, C: Codec> Decoder<R, C> {
+    pub fn new(inner: R, codec: C) -> Self {
+        Self {
+            inner,
+            codec,
+            input: vec![0; CHUNK_SIZE],
+            out: Vec::with_capacity(CHUNK_SIZE),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    pub(crate) fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    // decode the next chunk of `inner` into `out`
+    fn fill(&mut self) -> Result<(), io::Error> {
+        self.out.clear();
+        self.pos = 0;
+        let n = loop {
+            match self.inner.read(&mut self.input) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        self.done = n == 0;
+        self.codec.decode(&self.input[..n], n == 0, &mut self.out).map_err(|e| {
+            self.done = true;
+            e.into()
+        })
+    }
+}
+
+impl<R: Read, C: Codec> Read for Decoder<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.out.len() && !self.done {
+            self.fill()?;
+        }
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,63 @@
+/*
+ * module: error
+ */
+
+use std::io;
+use thiserror::Error;
+
+// offsets are byte positions in the decoder input, counting skipped newlines
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("InvalidCharacter: {:?} at offset {offset}", char::from(*.byte))]
+    InvalidCharacter { offset: usize, byte: u8 },
+
+    #[error("InvalidPadding: at offset {0}")]
+    InvalidPadding(usize),
+
+    #[error("NonCanonical: trailing bits are not zero at offset {0}")]
+    NonCanonical(usize),
+
+    #[error("IncompleteGroup: final {len} chars at offset {offset} are not a whole group")]
+    IncompleteGroup { offset: usize, len: usize },
+
+    #[error("Overflow: group at offset {0} exceeds 32 bits")]
+    Overflow(usize),
+}
+
+impl DecodeError {
+    // the error with its offset within a group translated by `offsets`
+    pub(crate) fn at(self, offsets: &[usize; 4]) -> Self {
+        match self {
+            Self::InvalidCharacter { offset, byte } => Self::InvalidCharacter {
+                offset: offsets[offset],
+                byte,
+            },
+            Self::InvalidPadding(offset) => Self::InvalidPadding(offsets[offset]),
+            Self::NonCanonical(offset) => Self::NonCanonical(offsets[offset]),
+            Self::IncompleteGroup { offset, len } => Self::IncompleteGroup {
+                offset: offsets[offset],
+                len,
+            },
+            Self::Overflow(offset) => Self::Overflow(offsets[offset]),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("InvalidAlphabet: {0}")]
+    InvalidAlphabet(String),
+
+    #[error("InvalidVariant: {0}")]
+    InvalidVariant(String),
+
+    #[error("InvalidCodec: {0}")]
+    InvalidCodec(String),
+}
+
+// a DecodeError is returned from Read as ErrorKind::InvalidData, see io::Error::get_ref()
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * module: fast
+ */
+
+use crate::alphabet::{Alphabet, STANDARD};
+use crate::encode_group;
+
+//================================================
+// Engine encodes whole groups of 3 bytes
+//    Scalar -- a group at a time, encode_group()
+//    Wide   -- 2 groups at a time, 12 bit indices
+//              into a table of char pairs
+//    Ssse3  -- 4 groups at a time, x86_64 SSSE3
+//    Avx2   -- 8 groups at a time, x86_64 AVX2
+//
+// the SIMD engines compute the chars of an
+// alphabet which begins "A-Za-z0-9" (standard,
+// URL safe), other alphabets use Wide
+//================================================
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    Scalar,
+    Wide,
+    Ssse3,
+    Avx2,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 4] = [Self::Scalar, Self::Wide, Self::Ssse3, Self::Avx2];
+
+    // whether this CPU has the instructions of the engine, detected at runtime
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Scalar | Self::Wide => true,
+            #[cfg(target_arch = "x86_64")]
+            Self::Ssse3 => is_x86_feature_detected!("ssse3"),
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Self::Ssse3 | Self::Avx2 => false,
+        }
+    }
+
+    // the fastest engine of this CPU
+    pub fn detect() -> Self {
+        [Self::Avx2, Self::Ssse3]
+            .into_iter()
+            .find(|engine| engine.is_available())
+            .unwrap_or(Self::Wide)
+    }
+}
+
+// FastEncoder encodes the whole groups of its input with an engine, and a table of the
+// char pairs of the alphabet
+pub struct FastEncoder {
+    engine: Engine,
+    alphabet: Alphabet,
+    pairs: Box<[[u8; 2]; 4096]>,
+}
+
+impl FastEncoder {
+    // the fastest engine for `alphabet`
+    pub fn new(alphabet: &Alphabet) -> Self {
+        Self::with_engine(alphabet, Engine::detect())
+            .or_else(|| Self::with_engine(alphabet, Engine::Wide))
+            .expect("Wide encodes any alphabet")
+    }
+
+    // None when the engine is not available, or the SIMD engines can't compute the alphabet
+    pub fn with_engine(alphabet: &Alphabet, engine: Engine) -> Option<Self> {
+        let simd = matches!(engine, Engine::Ssse3 | Engine::Avx2);
+        if !engine.is_available() || (simd && alphabet.chars()[..62] != STANDARD.chars()[..62]) {
+            return None;
+        }
+        let chars = alphabet.chars();
+        let mut pairs = Box::new([[0; 2]; 4096]);
+        for (i, pair) in pairs.iter_mut().enumerate() {
+            *pair = [chars[i >> 6], chars[i & 0x3f]];
+        }
+        Some(Self {
+            engine,
+            alphabet: alphabet.clone(),
+            pairs,
+        })
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    // append the 4 chars of each whole group of `src` to `dst`, returns the bytes encoded
+    pub fn encode(&self, src: &[u8], dst: &mut Vec<u8>) -> usize {
+        let len = src.len() / 3 * 3;
+        let start = dst.len();
+        dst.resize(start + len / 3 * 4, 0);
+        let (src, out) = (&src[..len], &mut dst[start..]);
+
+        // the SIMD engines leave the final groups, which they would read past, to Wide
+        let done = match self.engine {
+            Engine::Scalar => {
+                for (group, chars) in src.chunks_exact(3).zip(out.chunks_exact_mut(4)) {
+                    let chars: &mut [u8; 4] = chars.try_into().expect("4 chars");
+                    encode_group([group[0], group[1], group[2]], chars, 3, &self.alphabet);
+                }
+                len
+            }
+            Engine::Wide => 0,
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: with_engine() checked that the CPU has the instructions
+            Engine::Ssse3 => unsafe { x86::encode_ssse3(src, out, self.alphabet.chars()) },
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: as Ssse3
+            Engine::Avx2 => unsafe { x86::encode_avx2(src, out, self.alphabet.chars()) },
+            #[cfg(not(target_arch = "x86_64"))]
+            Engine::Ssse3 | Engine::Avx2 => unreachable!("not available"),
+        };
+        self.encode_wide(&src[done..], &mut out[done / 3 * 4..]);
+        len
+    }
+
+    // 2 groups from a big endian u64 of 8 bytes, then a group from 3 bytes
+    fn encode_wide(&self, src: &[u8], out: &mut [u8]) {
+        let wide = src.len().saturating_sub(2) / 6;
+        for i in 0..wide {
+            let bytes: [u8; 8] = src[i * 6..i * 6 + 8].try_into().expect("8 bytes");
+            let n = u64::from_be_bytes(bytes);
+            for (j, shift) in [52, 40, 28, 16].into_iter().enumerate() {
+                let k = i * 8 + j * 2;
+                out[k..k + 2].copy_from_slice(&self.pairs[(n >> shift & 0xfff) as usize]);
+            }
+        }
+        let (src, out) = (&src[wide * 6..], &mut out[wide * 8..]);
+        for (group, chars) in src.chunks_exact(3).zip(out.chunks_exact_mut(4)) {
+            let n = (group[0] as usize) << 16 | (group[1] as usize) << 8 | group[2] as usize;
+            chars[..2].copy_from_slice(&self.pairs[n >> 12]);
+            chars[2..].copy_from_slice(&self.pairs[n & 0xfff]);
+        }
+    }
+}
+
+// Muła and Lemire, "Faster Base64 Encoding and Decoding using AVX2 Instructions" (2018)
+//
+// the bytes of each group are shuffled into a u32, [b1 b0 b2 b1] (little endian), the 4 6 bit
+// indices are moved into the 4 bytes with multiplies, then each index is offset to its char:
+// 0..26 by 'A', 26..52 by 'a' - 26, 52..62 by '0' - 52, 62 and 63 by the alphabet's chars
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    // the offsets of the ranges, indexed by the saturated (index - 51) with 13 for 0..26
+    fn range_offsets(chars: &[u8; 64]) -> [i8; 16] {
+        let mut offsets = [0i8; 16];
+        offsets[0] = (b'a' as i8).wrapping_sub(26);
+        offsets[1..11].fill((b'0' as i8).wrapping_sub(52));
+        offsets[11] = (chars[62] as i8).wrapping_sub(62);
+        offsets[12] = (chars[63] as i8).wrapping_sub(63);
+        offsets[13] = b'A' as i8;
+        offsets
+    }
+
+    // encode 4 groups in each 128 bit lane
+    #[target_feature(enable = "ssse3")]
+    unsafe fn encode_lane(input: __m128i, offsets: __m128i) -> __m128i {
+        let input = _mm_shuffle_epi8(input, _mm_set_epi8(10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1));
+        let t0 = _mm_and_si128(input, _mm_set1_epi32(0x0fc0fc00));
+        let t1 = _mm_mulhi_epu16(t0, _mm_set1_epi32(0x04000040));
+        let t2 = _mm_and_si128(input, _mm_set1_epi32(0x003f03f0));
+        let t3 = _mm_mullo_epi16(t2, _mm_set1_epi32(0x01000010));
+        let indices = _mm_or_si128(t1, t3);
+
+        let reduced = _mm_subs_epu8(indices, _mm_set1_epi8(51));
+        let less = _mm_cmpgt_epi8(_mm_set1_epi8(26), indices);
+        let reduced = _mm_or_si128(reduced, _mm_and_si128(less, _mm_set1_epi8(13)));
+        _mm_add_epi8(_mm_shuffle_epi8(offsets, reduced), indices)
+    }
+
+    // returns the bytes encoded, a load of 16 bytes encodes 12
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn encode_ssse3(src: &[u8], out: &mut [u8], chars: &[u8; 64]) -> usize {
+        let offsets = _mm_loadu_si128(range_offsets(chars).as_ptr() as *const __m128i);
+        let mut i = 0;
+        while i + 16 <= src.len() {
+            let input = _mm_loadu_si128(src[i..i + 16].as_ptr() as *const __m128i);
+            let encoded = encode_lane(input, offsets);
+            _mm_storeu_si128(out[i / 3 * 4..i / 3 * 4 + 16].as_mut_ptr() as *mut __m128i, encoded);
+            i += 12;
+        }
+        i
+    }
+
+    // returns the bytes encoded, 2 loads of 16 bytes encode 24
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn encode_avx2(src: &[u8], out: &mut [u8], chars: &[u8; 64]) -> usize {
+        let offsets = _mm_loadu_si128(range_offsets(chars).as_ptr() as *const __m128i);
+        let offsets = _mm256_broadcastsi128_si256(offsets);
+        let shuffle = _mm256_set_epi8(
+            10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1, 10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1,
+        );
+        let mut i = 0;
+        while i + 28 <= src.len() {
+            let lo = _mm_loadu_si128(src[i..i + 16].as_ptr() as *const __m128i);
+            let hi = _mm_loadu_si128(src[i + 12..i + 28].as_ptr() as *const __m128i);
+            let input = _mm256_shuffle_epi8(_mm256_inserti128_si256(_mm256_castsi128_si256(lo), hi, 1), shuffle);
+
+            let t0 = _mm256_and_si256(input, _mm256_set1_epi32(0x0fc0fc00));
+            let t1 = _mm256_mulhi_epu16(t0, _mm256_set1_epi32(0x04000040));
+            let t2 = _mm256_and_si256(input, _mm256_set1_epi32(0x003f03f0));
+            let t3 = _mm256_mullo_epi16(t2, _mm256_set1_epi32(0x01000010));
+            let indices = _mm256_or_si256(t1, t3);
+
+            let reduced = _mm256_subs_epu8(indices, _mm256_set1_epi8(51));
+            let less = _mm256_cmpgt_epi8(_mm256_set1_epi8(26), indices);
+            let reduced = _mm256_or_si256(reduced, _mm256_and_si256(less, _mm256_set1_epi8(13)));
+            let encoded = _mm256_add_epi8(_mm256_shuffle_epi8(offsets, reduced), indices);
+
+            _mm256_storeu_si256(out[i / 3 * 4..i / 3 * 4 + 32].as_mut_ptr() as *mut __m256i, encoded);
+            i += 24;
+        }
+        i
+    }
+}
@@ -0,0 +1,304 @@
+use std::io::{self, Read, Write};
+
+pub mod alphabet;
+pub mod base58;
+pub mod base64;
+pub mod base85;
+pub mod bits;
+pub mod codec;
+pub mod error;
+pub mod fast;
+pub mod xxd;
+pub use alphabet::{Alphabet, Variant};
+pub use base64::Base64;
+pub use codec::{Codec, CodecType, Decoder, Encoder};
+pub use error::{ConfigError, DecodeError};
+pub use fast::{Engine, FastEncoder};
+
+use alphabet::{INVALID, MIME_COLUMNS, STANDARD};
+
+// Base64 pad character ("=")
+pub(crate) const PAD_CHAR: u8 = 61;
+
+/* Algorithm using shifting:
+ *
+ * bytes[0] = 'A'
+ * bytes[1] = 'B'
+ * bytes[2] = 'C'
+ *
+ * b64table[bytes[0] >> 2]                               //  b64table[16] == 'Q'
+ * b64table[((bytes[0] << 4) & 0x30) | (bytes[1] >> 4)]  //  b64table[20] == 'U'
+ * b64table[((bytes[1] << 2) & 0x3c) | (bytes[2] >> 6)]  //  b64table[9]  == 'J'
+ * b64table[bytes[2] & 0x3f]                             //  b64table[3]  == 'D'
+ *
+ * --------------------------------------------------------------------------------
+ *
+ * Algorithm using a C union
+ *
+ * typedef unsigned char uchar;
+ *
+ * typedef union {
+ *     uchar bytes[3];
+ *     struct {
+ *         unsigned d:6;
+ *         unsigned c:6;
+ *         unsigned b:6;
+ *         unsigned a:6;
+ *     } u;
+ * } B64;
+ *
+ * Union of 24 bits (3 bytes) and four 6-bit ints for Base64 encoding
+ *
+ *     'A'       'B'       'C'
+ *     65        66        67
+ *   bytes[2]  bytes[1]  bytes[0]  <===== "ABC" loaded in reverse of shifting technique
+ *   --------  --------  --------
+ *   01000001  01000010  01000011
+ *   ||||||||  ||||||||  ||||||||
+ *   ||||||||  ||||||||  ||++++++ u.d == 000011 == b64table[3] == 'D'
+ *   ||||||||  ||||++++  ++ u.c == 001001 == b64table[9] == 'J'
+ *   ||||||++  ++++ u.b == 010100 == b64table[20] == 'U'
+ *   ++++++ u.a == 010000 == b64table[16] == 'Q'
+ *
+ */
+
+pub fn b64_encode(src: [u8; 3], dst: &mut [u8; 4], n: usize) {
+    encode_group(src, dst, n, &STANDARD)
+}
+
+// encode `n` bytes of `src` with the chars of `alphabet`, a group of 1 or 2 bytes is padded
+fn encode_group(src: [u8; 3], dst: &mut [u8; 4], n: usize, alphabet: &Alphabet) {
+    // assert!(0x30 == 0b0011_0000);
+    // assert!(0x3c == 0b0011_1100);
+    // assert!(0x3f == 0b0011_1111);
+
+    dst[0] = alphabet.char(src[0] >> 2);
+    match n {
+        1 => {
+            dst[1] = alphabet.char(src[0] << 4 & 0b0011_0000);
+            dst[2] = PAD_CHAR;
+            dst[3] = PAD_CHAR;
+        }
+
+        2 => {
+            dst[1] = alphabet.char((src[0] << 4 & 0b0011_0000) | src[1] >> 4);
+            dst[2] = alphabet.char(src[1] << 2 & 0b0011_1100);
+            dst[3] = PAD_CHAR;
+        }
+
+        _ => {
+            dst[1] = alphabet.char((src[0] << 4 & 0b0011_0000) | src[1] >> 4);
+            dst[2] = alphabet.char((src[1] << 2 & 0b0011_1100) | src[2] >> 6);
+            dst[3] = alphabet.char(src[2] & 0b0011_1111);
+        }
+    }
+}
+
+// decode a group of 4 chars, `dst` receives 1 to 3 bytes
+// error offsets are positions in `src`
+pub fn b64_decode(src: [u8; 4], dst: &mut [u8; 3]) -> Result<usize, DecodeError> {
+    decode_group(src, dst, false, &STANDARD)
+}
+
+// `lenient` accepts non-zero trailing bits in a padded group
+fn decode_group(src: [u8; 4], dst: &mut [u8; 3], lenient: bool, alphabet: &Alphabet) -> Result<usize, DecodeError> {
+    // assert!(0x03 == 0b0000_0011);
+    // assert!(0x0f == 0b0000_1111);
+
+    // the 6 bit value of src[i], '=' is only valid as padding in src[2] and src[3]
+    let value = |i: usize| match alphabet.value(src[i]) {
+        INVALID if src[i] == PAD_CHAR => Err(DecodeError::InvalidPadding(i)),
+        INVALID => Err(DecodeError::InvalidCharacter {
+            offset: i,
+            byte: src[i],
+        }),
+        v => Ok(v),
+    };
+
+    let a = value(0)?;
+    let b = value(1)?;
+    dst[0] = (a << 2) | (b >> 4 & 0b0000_0011);
+
+    match (src[2], src[3]) {
+        (PAD_CHAR, PAD_CHAR) => match b & 0b0000_1111 {
+            0 => Ok(1),
+            _ if lenient => Ok(1),
+            _ => Err(DecodeError::NonCanonical(1)),
+        },
+        (PAD_CHAR, _) => Err(DecodeError::InvalidPadding(2)),
+        (_, PAD_CHAR) => {
+            let c = value(2)?;
+            dst[1] = (b << 4) | (c >> 2 & 0b0000_1111);
+            match c & 0b0000_0011 {
+                0 => Ok(2),
+                _ if lenient => Ok(2),
+                _ => Err(DecodeError::NonCanonical(2)),
+            }
+        }
+        _ => {
+            let c = value(2)?;
+            let d = value(3)?;
+            dst[1] = (b << 4) | (c >> 2 & 0b0000_1111);
+            dst[2] = (c << 6) | d;
+            Ok(3)
+        }
+    }
+}
+
+// ==============================================================
+// Streaming adapters, memory use is constant in the size of the input
+//
+// Base64Encoder<W> encodes the bytes written to it into W
+// Base64Decoder<R> decodes the Base64 read from R
+// ==============================================================
+
+// size of the encoded/decoded chunks written to or read from the inner stream
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Base64Encoder is a Write adapter, an Encoder of the Base64 codec, finish() writes the final group
+//
+// let mut encoder = Base64Encoder::new(io::stdout()).variant(Variant::Url).pad(false);
+// io::copy(&mut file, &mut encoder)?;
+// encoder.finish()?;
+pub struct Base64Encoder<W: Write> {
+    encoder: Encoder<W, Base64>,
+}
+
+impl<W: Write> Base64Encoder<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            encoder: Encoder::new(inner, Base64::new()),
+        }
+    }
+
+    fn codec(mut self, f: impl FnOnce(Base64) -> Base64) -> Self {
+        let codec = self.encoder.codec_mut();
+        *codec = f(std::mem::take(codec));
+        self
+    }
+
+    // the alphabet and padding of `variant`, Mime also wraps at 76 columns with CRLF newlines
+    pub fn variant(self, variant: Variant) -> Self {
+        let encoder = self.codec(|codec| codec.variant(variant));
+        match variant {
+            Variant::Mime => encoder.wrap(MIME_COLUMNS).crlf(true),
+            _ => encoder,
+        }
+    }
+
+    pub fn alphabet(self, alphabet: Alphabet) -> Self {
+        self.codec(|codec| codec.alphabet(alphabet))
+    }
+
+    // pad the final group with '=' to 4 chars
+    pub fn pad(self, pad: bool) -> Self {
+        self.codec(|codec| codec.pad(pad))
+    }
+
+    // end the wrapped lines with "\r\n" rather than "\n"
+    pub fn crlf(self, crlf: bool) -> Self {
+        Self {
+            encoder: self.encoder.crlf(crlf),
+        }
+    }
+
+    // break the output into lines of `columns` chars, there is no final newline
+    pub fn wrap(self, columns: usize) -> Self {
+        Self {
+            encoder: self.encoder.wrap(columns),
+        }
+    }
+
+    // encode the final group, flush, and return the inner writer
+    pub fn finish(self) -> io::Result<W> {
+        self.encoder.finish()
+    }
+}
+
+impl<W: Write> Write for Base64Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.encoder.write(buf)
+    }
+
+    // flushes the whole groups, an incomplete group waits for more input or finish()
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+// Base64Decoder is a Read adapter, a Decoder of the Base64 codec, whitespace
+// (' ', '\t', '\n', '\r', '\x0c') is ignored
+//
+// PEM armor lines, "-----BEGIN X-----" and "-----END X-----", are skipped, the data of each
+// BEGIN/END block is one stream (a certificate chain decodes to the concatenated DER)
+//
+// let mut decoder = Base64Decoder::new(file).variant(Variant::Url);
+// io::copy(&mut decoder, &mut io::stdout())?;
+//
+// invalid input is an ErrorKind::InvalidData io::Error wrapping a DecodeError
+pub struct Base64Decoder<R: Read> {
+    decoder: Decoder<R, Base64>,
+}
+
+impl<R: Read> Base64Decoder<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            decoder: Decoder::new(inner, Base64::new()),
+        }
+    }
+
+    fn codec(mut self, f: impl FnOnce(Base64) -> Base64) -> Self {
+        let codec = self.decoder.codec_mut();
+        *codec = f(std::mem::take(codec));
+        self
+    }
+
+    // the alphabet and padding of `variant`
+    pub fn variant(self, variant: Variant) -> Self {
+        self.codec(|codec| codec.variant(variant))
+    }
+
+    pub fn alphabet(self, alphabet: Alphabet) -> Self {
+        self.codec(|codec| codec.alphabet(alphabet))
+    }
+
+    // require the final group to be padded, unpadded input ends with a group of 2 or 3 chars
+    // (padding is accepted either way)
+    pub fn pad(self, pad: bool) -> Self {
+        self.codec(|codec| codec.pad(pad))
+    }
+
+    // skip the data integrity checks: non-zero trailing bits, data after padding,
+    // and a final group without padding are decoded, a final single char is dropped
+    pub fn lenient(self, lenient: bool) -> Self {
+        self.codec(|codec| codec.lenient(lenient))
+    }
+
+    // skip bytes which are not in the alphabet (as GNU base64 -i)
+    pub fn ignore_garbage(self, ignore_garbage: bool) -> Self {
+        self.codec(|codec| codec.ignore_garbage(ignore_garbage))
+    }
+}
+
+impl<R: Read> Read for Base64Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+// Base64 of `bytes`
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoder = Base64Encoder::new(vec![]);
+    encoder.write_all(bytes).expect("write to a Vec");
+    String::from_utf8(encoder.finish().expect("write to a Vec")).expect("Base64 is ASCII")
+}
+
+// the bytes of the Base64 `text`
+pub fn decode(text: &[u8]) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    Base64Decoder::new(text).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test;
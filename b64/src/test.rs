@@ -5,9 +5,9 @@ fn test_basic() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    src[0] = 'A' as u8;
-    src[1] = 'B' as u8;
-    src[2] = 'C' as u8;
+    src[0] = b'A';
+    src[1] = b'B';
+    src[2] = b'C';
 
     b64_encode(src, &mut dst, 3);
 
@@ -22,12 +22,12 @@ fn test_basic_decode() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    dst[0] = 'Q' as u8;
-    dst[1] = 'U' as u8;
-    dst[2] = 'J' as u8;
-    dst[3] = 'D' as u8;
+    dst[0] = b'Q';
+    dst[1] = b'U';
+    dst[2] = b'J';
+    dst[3] = b'D';
 
-    let nbytes = b64_decode(dst, &mut src);
+    let nbytes = b64_decode(dst, &mut src).unwrap();
 
     assert!(nbytes == 3);
     assert!(src[0] as char == 'A');
@@ -40,9 +40,9 @@ fn test_padded_encode1() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    src[0] = 'A' as u8;
-    src[1] = '*' as u8; // invalid Base64 char
-    src[2] = '*' as u8; // invalid Base64 char
+    src[0] = b'A';
+    src[1] = b'*'; // invalid Base64 char
+    src[2] = b'*'; // invalid Base64 char
 
     b64_encode(src, &mut dst, 1);
 
@@ -57,12 +57,12 @@ fn test_padded_decode1() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    dst[0] = 'Q' as u8;
-    dst[1] = 'Q' as u8;
-    dst[2] = '=' as u8;
-    dst[3] = '=' as u8;
+    dst[0] = b'Q';
+    dst[1] = b'Q';
+    dst[2] = b'=';
+    dst[3] = b'=';
 
-    let nbytes = b64_decode(dst, &mut src);
+    let nbytes = b64_decode(dst, &mut src).unwrap();
 
     assert!(nbytes == 1);
     assert!(src[0] as char == 'A')
@@ -73,9 +73,9 @@ fn test_padded_encode2() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    src[0] = 'A' as u8;
-    src[1] = 'B' as u8;
-    src[2] = '*' as u8; // invalid Base64 char
+    src[0] = b'A';
+    src[1] = b'B';
+    src[2] = b'*'; // invalid Base64 char
 
     b64_encode(src, &mut dst, 2);
 
@@ -90,14 +90,629 @@ fn test_padded_decode2() {
     let mut src: [u8; 3] = [0; 3];
     let mut dst: [u8; 4] = [0; 4];
 
-    dst[0] = 'Q' as u8;
-    dst[1] = 'U' as u8;
-    dst[2] = 'I' as u8;
-    dst[3] = '=' as u8;
+    dst[0] = b'Q';
+    dst[1] = b'U';
+    dst[2] = b'I';
+    dst[3] = b'=';
 
-    let nbytes = b64_decode(dst, &mut src);
+    let nbytes = b64_decode(dst, &mut src).unwrap();
 
     assert!(nbytes == 2);
     assert!(src[0] as char == 'A');
     assert!(src[1] as char == 'B')
 }
+
+// a Read which returns at most `n` bytes per read()
+struct Trickle<'a>(&'a [u8], usize);
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.1.min(buf.len()).min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_rfc4648_vectors() {
+    for (plain, encoded) in [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ] {
+        assert_eq!(encode(plain.as_bytes()), encoded);
+        assert_eq!(decode(encoded.as_bytes()).unwrap(), plain.as_bytes());
+    }
+}
+
+#[test]
+fn test_streaming_round_trip() {
+    let data = (0..CHUNK_SIZE * 2 + 7).map(|i| (i * 7 % 256) as u8).collect::<Vec<_>>();
+    let encoded = encode(&data);
+
+    // writes of every size encode the same as one write
+    for size in [1, 2, 3, 4, 1000, CHUNK_SIZE + 1] {
+        let mut encoder = Base64Encoder::new(vec![]);
+        for chunk in data.chunks(size) {
+            encoder.write_all(chunk).unwrap();
+        }
+        assert_eq!(encoder.finish().unwrap(), encoded.as_bytes(), "write size {size}");
+    }
+
+    // reads of every size decode the same
+    for size in [1, 3, 5, 4096, CHUNK_SIZE + 1] {
+        let mut decoded = vec![];
+        Base64Decoder::new(Trickle(encoded.as_bytes(), size))
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data, "read size {size}");
+    }
+}
+
+#[test]
+fn test_wrap() {
+    let data = [0u8; 57 * 2];
+    let mut encoder = Base64Encoder::new(vec![]).wrap(76);
+    encoder.write_all(&data).unwrap();
+    let wrapped = String::from_utf8(encoder.finish().unwrap()).unwrap();
+    let lines = wrapped.split('\n').collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "no newline after the last line");
+    assert!(lines.iter().all(|line| line.len() == 76));
+    assert_eq!(decode(wrapped.as_bytes()).unwrap(), data);
+}
+
+#[test]
+fn test_incomplete_group() {
+    assert!(decode(b"Zm9vY").is_err());
+    let mut decoded = vec![];
+    Base64Decoder::new(&b"Zm9vY"[..])
+        .lenient(true)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, b"foo");
+}
+
+// the DecodeError of a failed decode
+fn decode_error(mut decoder: Base64Decoder<&[u8]>) -> DecodeError {
+    let e = decoder.read_to_end(&mut vec![]).unwrap_err();
+    *e.get_ref()
+        .and_then(|e| e.downcast_ref::<DecodeError>())
+        .expect("a DecodeError")
+}
+
+#[test]
+fn test_decode_errors() {
+    let error = |text: &'static str| decode_error(Base64Decoder::new(text.as_bytes()));
+
+    // below '+' no longer underflows, inside the table range is no longer 0
+    assert_eq!(
+        error("Zm9v\n*m9v"),
+        DecodeError::InvalidCharacter { offset: 5, byte: b'*' }
+    );
+    assert_eq!(
+        error("Zm9v:m9v"),
+        DecodeError::InvalidCharacter { offset: 4, byte: b':' }
+    );
+    assert_eq!(
+        error("Zm9v.Zm9v"),
+        DecodeError::InvalidCharacter { offset: 4, byte: b'.' }
+    );
+    assert_eq!(
+        error("Z\u{e9}9v"),
+        DecodeError::InvalidCharacter { offset: 1, byte: 0xc3 }
+    );
+
+    assert_eq!(error("Zm=v"), DecodeError::InvalidPadding(2));
+    assert_eq!(error("Z==="), DecodeError::InvalidPadding(1));
+    assert_eq!(error("Zg==Zm9v"), DecodeError::InvalidPadding(4));
+    assert_eq!(error("Zm9v="), DecodeError::InvalidPadding(4));
+    assert_eq!(error("Zh=="), DecodeError::NonCanonical(1));
+    assert_eq!(error("Zm9="), DecodeError::NonCanonical(2));
+    assert_eq!(error("Zm9vY\nm"), DecodeError::IncompleteGroup { offset: 4, len: 2 });
+
+    assert_eq!(
+        DecodeError::InvalidCharacter { offset: 5, byte: b'*' }.to_string(),
+        "InvalidCharacter: '*' at offset 5"
+    );
+}
+
+#[test]
+fn test_lenient_and_ignore_garbage() {
+    let decode_with = |text: &str, lenient: bool, ignore_garbage: bool| {
+        let mut decoded = vec![];
+        Base64Decoder::new(text.as_bytes())
+            .lenient(lenient)
+            .ignore_garbage(ignore_garbage)
+            .read_to_end(&mut decoded)
+            .map(|_| decoded)
+    };
+
+    assert_eq!(decode_with("Zh==", true, false).unwrap(), b"f");
+    assert_eq!(decode_with("Zg==Zm9v", true, false).unwrap(), b"ffoo");
+    assert_eq!(decode_with("Zm9vYmE", true, false).unwrap(), b"fooba");
+    assert!(decode_with("Zm9v*", true, false).is_err());
+
+    assert_eq!(decode_with("Zm 9v\t*Ym\u{e9}Fy!", false, true).unwrap(), b"foobar");
+    assert!(decode_with("Zm=9v", false, true).is_err());
+}
+
+const VARIANTS: [Variant; 5] = [
+    Variant::Standard,
+    Variant::Url,
+    Variant::Mime,
+    Variant::Bcrypt,
+    Variant::Crypt,
+];
+
+fn encode_with(bytes: &[u8], variant: Variant, pad: bool) -> String {
+    let mut encoder = Base64Encoder::new(vec![]).variant(variant).pad(pad);
+    encoder.write_all(bytes).unwrap();
+    String::from_utf8(encoder.finish().unwrap()).unwrap()
+}
+
+fn decode_with(text: &str, variant: Variant, pad: bool) -> io::Result<Vec<u8>> {
+    let mut decoded = vec![];
+    Base64Decoder::new(text.as_bytes())
+        .variant(variant)
+        .pad(pad)
+        .read_to_end(&mut decoded)
+        .map(|_| decoded)
+}
+
+#[test]
+fn test_variant_round_trip() {
+    let bytes = (0..300u32).map(|i| (i * 97 + i / 7) as u8).collect::<Vec<_>>();
+    for variant in VARIANTS {
+        for pad in [true, false] {
+            for len in 0..bytes.len() {
+                let encoded = encode_with(&bytes[..len], variant, pad);
+                assert_eq!(
+                    decode_with(&encoded, variant, pad).unwrap(),
+                    &bytes[..len],
+                    "{variant:?} pad={pad} len={len}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_variant_vectors() {
+    let bytes = [0xfb, 0xff, 0xbf, 0xfb, 0xff];
+    assert_eq!(encode_with(&bytes, Variant::Standard, true), "+/+/+/8=");
+    assert_eq!(encode_with(&bytes, Variant::Url, true), "-_-_-_8=");
+    assert_eq!(encode_with(&bytes, Variant::Url, false), "-_-_-_8");
+    assert_eq!(encode_with(b"f", Variant::Standard, false), "Zg");
+    assert!(decode_with("-_-_", Variant::Standard, true).is_err());
+    assert!(decode_with("+/+/", Variant::Url, true).is_err());
+
+    // bcrypt and crypt are unpadded, '.' and '/' are the values 0 and 1
+    assert!(!Variant::Bcrypt.padded() && !Variant::Crypt.padded());
+    assert_eq!(encode_with(&[0, 0, 0], Variant::Bcrypt, false), "....");
+    assert_eq!(encode_with(&[0x04, 0x10, 0x41], Variant::Crypt, false), "////");
+    assert_eq!(encode_with(&[0xff, 0xff, 0xff, 0xff], Variant::Bcrypt, false), "99999u");
+    assert_eq!(encode_with(&[0xff, 0xff, 0xff, 0xff], Variant::Crypt, false), "zzzzzk");
+    // crypt is the alphabet only, bits are msb first, a crypt(3) hash of 0x01 would be "/..."
+    assert_eq!(encode_with(&[0x01, 0, 0], Variant::Crypt, false), ".E..");
+
+    // MIME lines are 76 chars ending in CRLF, without a final line ending
+    let mime = encode_with(&[0; 120], Variant::Mime, true);
+    assert_eq!(mime, format!("{0}\r\n{0}\r\n{1}", "A".repeat(76), "A".repeat(8)));
+
+    // a padded final group is accepted when unpadded, a group of 1 char is not
+    assert_eq!(decode_with("Zg==", Variant::Standard, false).unwrap(), b"f");
+    assert_eq!(decode_with("Zm9vYg", Variant::Standard, false).unwrap(), b"foob");
+    let error = |text: &'static str| decode_error(Base64Decoder::new(text.as_bytes()).pad(false));
+    assert_eq!(error("Zm9vY"), DecodeError::IncompleteGroup { offset: 4, len: 1 });
+    assert_eq!(error("Zh"), DecodeError::NonCanonical(1));
+}
+
+#[test]
+fn test_custom_alphabet() {
+    let chars = b"zyxwvutsrqponmlkjihgfedcbaZYXWVUTSRQPONMLKJIHGFEDCBA9876543210_-";
+    let alphabet = Alphabet::new(chars).unwrap();
+    assert_eq!(alphabet.chars(), chars);
+
+    let mut encoder = Base64Encoder::new(vec![]).alphabet(alphabet.clone());
+    encoder.write_all(b"foobar").unwrap();
+    let encoded = encoder.finish().unwrap();
+    assert_eq!(encoded, b"aN0EbNuB");
+
+    let mut decoded = vec![];
+    Base64Decoder::new(&encoded[..])
+        .alphabet(alphabet)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, b"foobar");
+
+    assert!(Alphabet::new(&chars[1..]).is_err());
+    assert!(Alphabet::new(&[b"=".as_slice(), &chars[1..]].concat()).is_err());
+    assert!(Alphabet::new(&[b" ".as_slice(), &chars[1..]].concat()).is_err());
+    assert_eq!(
+        Alphabet::new(&[b"y".as_slice(), &chars[1..]].concat())
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "InvalidAlphabet: {:?} repeats 'y'",
+            "y".to_string() + std::str::from_utf8(&chars[1..]).unwrap()
+        )
+    );
+    assert!("jwt".parse::<Variant>().is_err());
+    assert_eq!("url".parse::<Variant>().unwrap(), Variant::Url);
+}
+
+fn codec_encode(codec: CodecType, bytes: &[u8]) -> String {
+    let mut encoder = Encoder::new(vec![], codec.codec(true));
+    encoder.write_all(bytes).unwrap();
+    String::from_utf8(encoder.finish().unwrap()).unwrap()
+}
+
+fn codec_decode(codec: CodecType, text: &str) -> io::Result<Vec<u8>> {
+    let mut decoded = vec![];
+    Decoder::new(Trickle(text.as_bytes(), 3), codec.codec(true))
+        .read_to_end(&mut decoded)
+        .map(|_| decoded)
+}
+
+fn codec_error(codec: CodecType, text: &'static str) -> DecodeError {
+    let e = codec_decode(codec, text).unwrap_err();
+    *e.get_ref()
+        .and_then(|e| e.downcast_ref::<DecodeError>())
+        .expect("a DecodeError")
+}
+
+// each (bytes, encoding) is encoded and decoded
+fn assert_vectors(codec: CodecType, vectors: &[(&[u8], &str)]) {
+    for &(bytes, text) in vectors {
+        assert_eq!(codec_encode(codec, bytes), text, "{codec:?} {bytes:?}");
+        assert_eq!(codec_decode(codec, text).unwrap(), bytes, "{codec:?} {text:?}");
+    }
+}
+
+#[test]
+fn test_codec_round_trip() {
+    let bytes = (0..300u32).map(|i| (i * 97 + i / 7) as u8).collect::<Vec<_>>();
+    for name in codec::CODEC_NAMES {
+        let codec = name.parse::<CodecType>().unwrap();
+        for len in (0..bytes.len()).step_by(7).chain([1, 2, 3, 4]) {
+            let encoded = codec_encode(codec, &bytes[..len]);
+            assert_eq!(
+                codec_decode(codec, &encoded).unwrap(),
+                &bytes[..len],
+                "{name} len={len}"
+            );
+        }
+    }
+    assert!("base36".parse::<CodecType>().is_err());
+}
+
+#[test]
+fn test_base64_codec() {
+    // RFC 4648 §10, as Base64Encoder
+    assert_vectors(
+        CodecType::Base64,
+        &[
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ],
+    );
+    assert_eq!(
+        codec_error(CodecType::Base64, "Zm9vY"),
+        DecodeError::IncompleteGroup { offset: 4, len: 1 }
+    );
+    assert_eq!(codec_error(CodecType::Base64, "Zh=="), DecodeError::NonCanonical(1));
+    assert_eq!(
+        codec_error(CodecType::Base64, "Zg==Zg=="),
+        DecodeError::InvalidPadding(4)
+    );
+    assert_eq!(codec_error(CodecType::Base64, "Zm9v="), DecodeError::InvalidPadding(4));
+}
+
+#[test]
+fn test_base32_vectors() {
+    // RFC 4648 §10
+    assert_vectors(
+        CodecType::Base32,
+        &[
+            (b"", ""),
+            (b"f", "MY======"),
+            (b"fo", "MZXQ===="),
+            (b"foo", "MZXW6==="),
+            (b"foob", "MZXW6YQ="),
+            (b"fooba", "MZXW6YTB"),
+            (b"foobar", "MZXW6YTBOI======"),
+        ],
+    );
+    assert_eq!(codec_decode(CodecType::Base32, "MZXW6\nYTB").unwrap(), b"fooba");
+    assert_eq!(
+        codec_error(CodecType::Base32, "MZX====="),
+        DecodeError::InvalidPadding(3)
+    );
+    assert_eq!(
+        codec_error(CodecType::Base32, "MY====="),
+        DecodeError::IncompleteGroup { offset: 0, len: 7 }
+    );
+    assert_eq!(
+        codec_error(CodecType::Base32, "MZXW6"),
+        DecodeError::IncompleteGroup { offset: 0, len: 5 }
+    );
+    assert_eq!(
+        codec_error(CodecType::Base32, "mzxw6ytb"),
+        DecodeError::InvalidCharacter { offset: 0, byte: b'm' }
+    );
+
+    let mut encoder = Encoder::new(vec![], CodecType::Base32.codec(false));
+    encoder.write_all(b"foobar").unwrap();
+    assert_eq!(encoder.finish().unwrap(), b"MZXW6YTBOI");
+    let mut decoded = vec![];
+    Decoder::new(&b"MZXW6YTBOI"[..], CodecType::Base32.codec(false))
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, b"foobar");
+}
+
+#[test]
+fn test_crockford_vectors() {
+    assert_vectors(
+        CodecType::Crockford,
+        &[
+            (b"", ""),
+            (b"f", "CR"),
+            (b"foobar", "CSQPYRK1E8"),
+            (b"Hello, World!", "91JPRV3F5GG5EVVJDHJ22"),
+        ],
+    );
+    // case insensitive, 'I' 'L' are '1', 'O' is '0', and '-' is ignored
+    assert_eq!(codec_decode(CodecType::Crockford, "csqp-yrkie8").unwrap(), b"foobar");
+    assert_eq!(codec_decode(CodecType::Crockford, "CSQPYRKLE8").unwrap(), b"foobar");
+    assert_eq!(
+        codec_decode(CodecType::Crockford, "91JPRV3F5GG5EVVJDHJ22").unwrap(),
+        b"Hello, World!"
+    );
+    assert_eq!(
+        codec_error(CodecType::Crockford, "CU"),
+        DecodeError::InvalidCharacter { offset: 1, byte: b'U' }
+    );
+    assert_eq!(codec_error(CodecType::Crockford, "CS"), DecodeError::NonCanonical(1));
+}
+
+#[test]
+fn test_base58_vectors() {
+    // draft-msporny-base58
+    assert_vectors(
+        CodecType::Base58,
+        &[
+            (b"", ""),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                b"The quick brown fox jumps over the lazy dog.",
+                "USm3fpXnKG5EUBx2ndxBDMPVciP5hGey2Jh4NDv6gmeo1LkMeiKrLJUUBk6Z",
+            ),
+            (&[0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+            (&[0x00], "1"),
+        ],
+    );
+    assert_eq!(
+        codec_error(CodecType::Base58, "2NEpo0"),
+        DecodeError::InvalidCharacter { offset: 5, byte: b'0' }
+    );
+}
+
+#[test]
+fn test_base85_vectors() {
+    assert_vectors(
+        CodecType::Ascii85,
+        &[
+            (b"", ""),
+            (b"f", "Ac"),
+            (b"fo", "Ao@"),
+            (b"Man is distinguished", "9jqo^BlbD-BleB1DJ+*+F(f,q"),
+            (&[0, 0, 0, 0, 0, 0, 0, 0, b'x'], "zzGQ"),
+            (&[0xff; 4], "s8W-!"),
+        ],
+    );
+    assert_eq!(
+        codec_decode(CodecType::Ascii85, "9jqo^ BlbD-\nBleB1").unwrap(),
+        b"Man is disti"
+    );
+    assert_eq!(
+        codec_error(CodecType::Ascii85, "Azc"),
+        DecodeError::InvalidCharacter { offset: 1, byte: b'z' }
+    );
+    assert_eq!(codec_error(CodecType::Ascii85, "zs8W-\"!"), DecodeError::Overflow(1));
+    assert_eq!(
+        codec_error(CodecType::Ascii85, "9jqo^B"),
+        DecodeError::IncompleteGroup { offset: 5, len: 1 }
+    );
+
+    // ZeroMQ RFC 32
+    assert_vectors(
+        CodecType::Z85,
+        &[
+            (&[0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B], "HelloWorld"),
+            (&[0; 4], "00000"),
+            // the extension of RFC 32, a final block of 1 to 3 bytes as in Ascii85
+            (b"abc", "vpAZ"),
+            (b"abcde", "vpA.SwD"),
+        ],
+    );
+}
+
+#[test]
+fn test_hex_vectors() {
+    // RFC 4648 §10, Base16
+    assert_vectors(
+        CodecType::Hex,
+        &[
+            (b"", ""),
+            (b"f", "66"),
+            (b"fo", "666f"),
+            (b"foobar", "666f6f626172"),
+            (&[0xab, 0xcd], "abcd"),
+        ],
+    );
+    assert_eq!(codec_decode(CodecType::Hex, "66 6F\n6f\t62").unwrap(), b"foob");
+    assert_eq!(
+        codec_error(CodecType::Hex, "666"),
+        DecodeError::IncompleteGroup { offset: 2, len: 1 }
+    );
+    assert_eq!(
+        codec_error(CodecType::Hex, "6g"),
+        DecodeError::InvalidCharacter { offset: 1, byte: b'g' }
+    );
+
+    // as xxd
+    let dump = concat!(
+        "00000000: 4865 6c6c 6f2c 2057 6f72 6c64 210a 6162  Hello, World!.ab\n",
+        "00000010: 6364 6566 6768 696a 6b6c 6d6e 6f70 7172  cdefghijklmnopqr\n",
+        "00000020: 7374 7576 7778 797a                      stuvwxyz",
+    );
+    assert_vectors(
+        CodecType::Xxd,
+        &[(b"", ""), (b"Hello, World!\nabcdefghijklmnopqrstuvwxyz", dump)],
+    );
+    assert_eq!(
+        codec_decode(CodecType::Xxd, "00000000: 4142 43  ABC\n").unwrap(),
+        b"ABC"
+    );
+    assert_eq!(
+        codec_error(CodecType::Xxd, "00000000: 4142 4x  ABx"),
+        DecodeError::InvalidCharacter { offset: 16, byte: b'x' }
+    );
+    assert_eq!(
+        codec_error(CodecType::Xxd, "00000000: 4142 4\n"),
+        DecodeError::IncompleteGroup { offset: 15, len: 1 }
+    );
+}
+
+#[test]
+fn test_whitespace() {
+    assert_eq!(decode(b" Zm9v\tYmFy \r\n\x0cZm9v\n").unwrap(), b"foobarfoo");
+    assert_eq!(codec_decode(CodecType::Base32, " MZXW6\t YTB\r\n").unwrap(), b"fooba");
+    assert_eq!(
+        codec_decode(CodecType::Base58, "2NEpo7TZ RRrLZSi2U\n").unwrap(),
+        b"Hello World!"
+    );
+
+    let mut encoder = Base64Encoder::new(vec![]).wrap(4).crlf(true);
+    encoder.write_all(b"foobarfoo").unwrap();
+    assert_eq!(encoder.finish().unwrap(), b"Zm9v\r\nYmFy\r\nZm9v");
+    let mut encoder = Encoder::new(vec![], CodecType::Hex.codec(true)).wrap(4).crlf(true);
+    encoder.write_all(b"foob").unwrap();
+    assert_eq!(encoder.finish().unwrap(), b"666f\r\n6f62");
+}
+
+#[test]
+fn test_pem_armor() {
+    let pem = "-----BEGIN CERTIFICATE-----\r\nZm9v\r\nYg==\r\n-----END CERTIFICATE-----\r\n";
+    assert_eq!(decode(pem.as_bytes()).unwrap(), b"foob");
+
+    // a chain decodes to the concatenated data, the final END needs no newline
+    let chain = "-----BEGIN A-----\nZg==\n-----END A-----\n\n-----BEGIN B-----\nYmFy\n-----END B-----";
+    assert_eq!(decode(chain.as_bytes()).unwrap(), b"fbar");
+
+    let error = |text: &'static str| decode_error(Base64Decoder::new(text.as_bytes()));
+    assert_eq!(
+        error("-----BEGIN A-----\nZm9\n-----END A-----\n"),
+        DecodeError::IncompleteGroup { offset: 18, len: 3 }
+    );
+    assert_eq!(
+        error("Zm9v\n-----NOT ARMOR-----\n"),
+        DecodeError::InvalidCharacter { offset: 5, byte: b'-' }
+    );
+    assert_eq!(
+        error("Zm9v-----END A-----"),
+        DecodeError::InvalidCharacter { offset: 4, byte: b'-' }
+    );
+    assert_eq!(error("Zg==\nZg=="), DecodeError::InvalidPadding(5));
+
+    // '-' is data in the URL safe alphabet
+    let mut decoded = vec![];
+    Base64Decoder::new(&b"-_-_\n-_8="[..])
+        .variant(Variant::Url)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, [0xfb, 0xff, 0xbf, 0xfb, 0xff]);
+}
+
+// xorshift64, a repeatable stream of random bytes
+struct XorShift(u64);
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+// differential fuzzing: every engine available on this CPU encodes as the scalar engine
+#[test]
+fn test_fast_engines() {
+    let custom = Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!~").unwrap();
+    let alphabets = [STANDARD, alphabet::URL_SAFE, alphabet::BCRYPT, alphabet::CRYPT, custom];
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+    for alphabet in &alphabets {
+        let scalar = FastEncoder::with_engine(alphabet, Engine::Scalar).unwrap();
+        let engines = Engine::ALL
+            .iter()
+            .filter_map(|&engine| FastEncoder::with_engine(alphabet, engine))
+            .collect::<Vec<_>>();
+        for i in 0..2000 {
+            let len = match i % 4 {
+                0 => (rng.next() % 64) as usize,
+                _ => (rng.next() % 4096) as usize,
+            };
+            let bytes = rng.bytes(len);
+            let mut expected = vec![];
+            assert_eq!(scalar.encode(&bytes, &mut expected), len / 3 * 3);
+            for fast in &engines {
+                let mut encoded = vec![b'>'];
+                assert_eq!(fast.encode(&bytes, &mut encoded), len / 3 * 3);
+                assert_eq!(&encoded[1..], &expected, "{:?} {:?}", fast.engine(), bytes);
+            }
+        }
+    }
+
+    // through the encoder, in writes of random sizes with wrapping
+    for _ in 0..200 {
+        let len = (rng.next() % 10_000) as usize;
+        let bytes = rng.bytes(len);
+        let mut encoder = Base64Encoder::new(vec![]).wrap(76);
+        let mut rest = &bytes[..];
+        while !rest.is_empty() {
+            let n = (rng.next() as usize % 300).min(rest.len());
+            encoder.write_all(&rest[..n]).unwrap();
+            rest = &rest[n..];
+        }
+        let encoded = encoder.finish().unwrap();
+
+        let mut expected = vec![];
+        for (i, group) in bytes.chunks(3).enumerate() {
+            let mut dst = [0; 4];
+            b64_encode(
+                [group[0], *group.get(1).unwrap_or(&0), *group.get(2).unwrap_or(&0)],
+                &mut dst,
+                group.len(),
+            );
+            if i > 0 && i % 19 == 0 {
+                expected.push(b'\n');
+            }
+            expected.extend_from_slice(&dst);
+        }
+        assert_eq!(encoded, expected);
+        assert_eq!(decode(&encoded).unwrap(), bytes);
+    }
+}
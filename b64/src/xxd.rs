@@ -0,0 +1,120 @@
+/*
+ * module: xxd
+ */
+
+use crate::codec::Codec;
+use crate::error::DecodeError;
+
+const HEXCHARS: &[u8; 16] = b"0123456789abcdef";
+
+// bytes of a line, and the width of its hex columns: 8 groups of 2 bytes
+const LINE: usize = 16;
+const HEX_WIDTH: usize = 39;
+
+// Xxd is the hex dump of xxd, a line of 16 bytes is an offset, the bytes in hex and as text
+//
+// 00000000: 4865 6c6c 6f2c 2057 6f72 6c64 210a       Hello, World!.
+//
+// lines are separated by newlines, there is no final newline
+//
+// decoding reads the hex columns, which end at 2 spaces, as xxd -r, the line offsets are ignored
+#[derive(Clone, Debug, Default)]
+pub struct Xxd {
+    // encode: bytes of the current line, and the offset of the line
+    line: Vec<u8>,
+    address: usize,
+    // decode: the part of the line, the high nibble of a byte, and the input offset
+    column: Column,
+    nibble: Option<(u8, usize)>,
+    offset: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Column {
+    #[default]
+    Address,
+    Hex,
+    // a space in the hex column
+    Space,
+    Text,
+}
+
+impl Xxd {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dump_line(&mut self, out: &mut Vec<u8>) {
+        if self.address > 0 {
+            out.push(b'\n');
+        }
+        out.extend_from_slice(format!("{:08x}: ", self.address).as_bytes());
+        let start = out.len();
+        for (i, byte) in self.line.iter().enumerate() {
+            if i > 0 && i % 2 == 0 {
+                out.push(b' ');
+            }
+            out.push(HEXCHARS[(byte >> 4) as usize]);
+            out.push(HEXCHARS[(byte & 0x0f) as usize]);
+        }
+        out.resize(start + HEX_WIDTH + 2, b' ');
+        out.extend(self.line.iter().map(|&byte| match byte {
+            b' '..=b'~' => byte,
+            _ => b'.',
+        }));
+        self.address += self.line.len();
+        self.line.clear();
+    }
+}
+
+impl Codec for Xxd {
+    fn encode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) {
+        for &byte in input {
+            self.line.push(byte);
+            if self.line.len() == LINE {
+                self.dump_line(out);
+            }
+        }
+        if last && !self.line.is_empty() {
+            self.dump_line(out);
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for (i, &ch) in input.iter().enumerate() {
+            let offset = self.offset + i;
+            let invalid = DecodeError::InvalidCharacter { offset, byte: ch };
+            self.column = match (self.column, ch) {
+                (_, b'\n') => match self.nibble.take() {
+                    Some((_, offset)) => return Err(DecodeError::IncompleteGroup { offset, len: 1 }),
+                    None => Column::Address,
+                },
+                (Column::Address, b':') => Column::Hex,
+                (Column::Address, b'\r') => Column::Address,
+                (Column::Address, _) if ch.is_ascii_hexdigit() => Column::Address,
+                (Column::Address, _) => return Err(invalid),
+                (Column::Hex, b' ') => Column::Space,
+                (Column::Space, b' ') => Column::Text,
+                (Column::Hex | Column::Space, _) => {
+                    let value = match ch {
+                        b'0'..=b'9' => ch - b'0',
+                        b'a'..=b'f' => ch - b'a' + 10,
+                        b'A'..=b'F' => ch - b'A' + 10,
+                        _ => return Err(invalid),
+                    };
+                    match self.nibble.take() {
+                        Some((high, _)) => out.push(high << 4 | value),
+                        None => self.nibble = Some((value, offset)),
+                    }
+                    Column::Hex
+                }
+                (Column::Text, _) => Column::Text,
+            };
+        }
+        self.offset += input.len();
+        match (last, self.nibble) {
+            (true, Some((_, offset))) => Err(DecodeError::IncompleteGroup { offset, len: 1 }),
+            _ => Ok(()),
+        }
+    }
+}
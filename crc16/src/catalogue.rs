@@ -0,0 +1,142 @@
+use crate::engine::Crc;
+use std::fmt;
+
+// A CRC of the catalogue, the parameters of its Crc type, its names, and
+//    check   -- the CRC of "123456789"
+//    residue -- the register after a message and its CRC, before XOROUT
+#[derive(Debug)]
+pub struct Algorithm {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub width: u32,
+    pub poly: u16,
+    pub init: u16,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: u16,
+    pub check: u16,
+    pub residue: u16,
+    crc: fn(&[u8]) -> u16,
+}
+
+impl Algorithm {
+    pub fn checksum(&self, msg: &[u8]) -> u16 {
+        (self.crc)(msg)
+    }
+
+    // The algorithm with a name or alias, case insensitive
+    pub fn find(name: &str) -> Option<&'static Algorithm> {
+        CATALOGUE.iter().find(|algorithm| {
+            algorithm.name.eq_ignore_ascii_case(name)
+                || algorithm.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+// The catalogue format
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "width={} poly={:#06x} init={:#06x} refin={} refout={} xorout={:#06x} check={:#06x} residue={:#06x} name={:?}",
+            self.width, self.poly, self.init, self.refin, self.refout, self.xorout, self.check, self.residue, self.name
+        )?;
+        for alias in self.aliases {
+            write!(f, " alias={alias:?}")?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! algorithm {
+    (width=$width:literal poly=$poly:literal init=$init:literal refin=$refin:literal refout=$refout:literal
+     xorout=$xorout:literal check=$check:literal residue=$residue:literal
+     name=$name:literal $(alias=$alias:literal)*) => {
+        Algorithm {
+            name: $name,
+            aliases: &[$($alias),*],
+            width: $width,
+            poly: $poly,
+            init: $init,
+            refin: $refin,
+            refout: $refout,
+            xorout: $xorout,
+            check: $check,
+            residue: $residue,
+            crc: Crc::<$width, $poly, $init, $refin, $refout, $xorout>::checksum,
+        }
+    };
+}
+
+// The CRC-16 algorithms of the catalogue
+//
+// https://reveng.sourceforge.io/crc-catalogue/16.htm
+#[rustfmt::skip]
+pub static CATALOGUE: [Algorithm; 31] = [
+    algorithm!(width=16 poly=0x8005 init=0x0000 refin=true refout=true xorout=0x0000 check=0xbb3d residue=0x0000
+               name="CRC-16/ARC" alias="ARC" alias="CRC-16" alias="CRC-16/LHA" alias="CRC-IBM"),
+    algorithm!(width=16 poly=0xc867 init=0xffff refin=false refout=false xorout=0x0000 check=0x4c06 residue=0x0000
+               name="CRC-16/CDMA2000"),
+    algorithm!(width=16 poly=0x8005 init=0xffff refin=false refout=false xorout=0x0000 check=0xaee7 residue=0x0000
+               name="CRC-16/CMS"),
+    algorithm!(width=16 poly=0x8005 init=0x800d refin=false refout=false xorout=0x0000 check=0x9ecf residue=0x0000
+               name="CRC-16/DDS-110"),
+    algorithm!(width=16 poly=0x0589 init=0x0000 refin=false refout=false xorout=0x0001 check=0x007e residue=0x0589
+               name="CRC-16/DECT-R" alias="R-CRC-16"),
+    algorithm!(width=16 poly=0x0589 init=0x0000 refin=false refout=false xorout=0x0000 check=0x007f residue=0x0000
+               name="CRC-16/DECT-X" alias="X-CRC-16"),
+    algorithm!(width=16 poly=0x3d65 init=0x0000 refin=true refout=true xorout=0xffff check=0xea82 residue=0x66c5
+               name="CRC-16/DNP"),
+    algorithm!(width=16 poly=0x3d65 init=0x0000 refin=false refout=false xorout=0xffff check=0xc2b7 residue=0xa366
+               name="CRC-16/EN-13757"),
+    algorithm!(width=16 poly=0x1021 init=0xffff refin=false refout=false xorout=0xffff check=0xd64e residue=0x1d0f
+               name="CRC-16/GENIBUS" alias="CRC-16/DARC" alias="CRC-16/EPC" alias="CRC-16/EPC-C1G2"
+               alias="CRC-16/I-CODE"),
+    algorithm!(width=16 poly=0x1021 init=0x0000 refin=false refout=false xorout=0xffff check=0xce3c residue=0x1d0f
+               name="CRC-16/GSM"),
+    algorithm!(width=16 poly=0x1021 init=0xffff refin=false refout=false xorout=0x0000 check=0x29b1 residue=0x0000
+               name="CRC-16/IBM-3740" alias="CRC-16/AUTOSAR" alias="CRC-16/CCITT-FALSE"),
+    algorithm!(width=16 poly=0x1021 init=0xffff refin=true refout=true xorout=0xffff check=0x906e residue=0xf0b8
+               name="CRC-16/IBM-SDLC" alias="CRC-16/ISO-HDLC" alias="CRC-16/ISO-IEC-14443-3-B" alias="CRC-16/X-25"
+               alias="CRC-B" alias="X-25"),
+    algorithm!(width=16 poly=0x1021 init=0xc6c6 refin=true refout=true xorout=0x0000 check=0xbf05 residue=0x0000
+               name="CRC-16/ISO-IEC-14443-3-A" alias="CRC-A"),
+    algorithm!(width=16 poly=0x1021 init=0x0000 refin=true refout=true xorout=0x0000 check=0x2189 residue=0x0000
+               name="CRC-16/KERMIT" alias="CRC-16/BLUETOOTH" alias="CRC-16/CCITT" alias="CRC-16/CCITT-TRUE"
+               alias="CRC-16/V-41-LSB" alias="CRC-CCITT" alias="KERMIT"),
+    algorithm!(width=16 poly=0x6f63 init=0x0000 refin=false refout=false xorout=0x0000 check=0xbdf4 residue=0x0000
+               name="CRC-16/LJ1200"),
+    algorithm!(width=16 poly=0x5935 init=0xffff refin=false refout=false xorout=0x0000 check=0x772b residue=0x0000
+               name="CRC-16/M17"),
+    algorithm!(width=16 poly=0x8005 init=0x0000 refin=true refout=true xorout=0xffff check=0x44c2 residue=0xb001
+               name="CRC-16/MAXIM-DOW" alias="CRC-16/MAXIM"),
+    algorithm!(width=16 poly=0x1021 init=0xffff refin=true refout=true xorout=0x0000 check=0x6f91 residue=0x0000
+               name="CRC-16/MCRF4XX"),
+    algorithm!(width=16 poly=0x8005 init=0xffff refin=true refout=true xorout=0x0000 check=0x4b37 residue=0x0000
+               name="CRC-16/MODBUS" alias="MODBUS"),
+    algorithm!(width=16 poly=0x080b init=0xffff refin=true refout=true xorout=0x0000 check=0xa066 residue=0x0000
+               name="CRC-16/NRSC-5"),
+    algorithm!(width=16 poly=0x5935 init=0x0000 refin=false refout=false xorout=0x0000 check=0x5d38 residue=0x0000
+               name="CRC-16/OPENSAFETY-A"),
+    algorithm!(width=16 poly=0x755b init=0x0000 refin=false refout=false xorout=0x0000 check=0x20fe residue=0x0000
+               name="CRC-16/OPENSAFETY-B"),
+    algorithm!(width=16 poly=0x1dcf init=0xffff refin=false refout=false xorout=0xffff check=0xa819 residue=0xe394
+               name="CRC-16/PROFIBUS" alias="CRC-16/IEC-61158-2"),
+    algorithm!(width=16 poly=0x1021 init=0xb2aa refin=true refout=true xorout=0x0000 check=0x63d0 residue=0x0000
+               name="CRC-16/RIELLO"),
+    algorithm!(width=16 poly=0x1021 init=0x1d0f refin=false refout=false xorout=0x0000 check=0xe5cc residue=0x0000
+               name="CRC-16/SPI-FUJITSU" alias="CRC-16/AUG-CCITT"),
+    algorithm!(width=16 poly=0x8bb7 init=0x0000 refin=false refout=false xorout=0x0000 check=0xd0db residue=0x0000
+               name="CRC-16/T10-DIF"),
+    algorithm!(width=16 poly=0xa097 init=0x0000 refin=false refout=false xorout=0x0000 check=0x0fb3 residue=0x0000
+               name="CRC-16/TELEDISK"),
+    algorithm!(width=16 poly=0x1021 init=0x89ec refin=true refout=true xorout=0x0000 check=0x26b1 residue=0x0000
+               name="CRC-16/TMS37157"),
+    algorithm!(width=16 poly=0x8005 init=0x0000 refin=false refout=false xorout=0x0000 check=0xfee8 residue=0x0000
+               name="CRC-16/UMTS" alias="CRC-16/BUYPASS" alias="CRC-16/VERIFONE"),
+    algorithm!(width=16 poly=0x8005 init=0xffff refin=true refout=true xorout=0xffff check=0xb4c8 residue=0xb001
+               name="CRC-16/USB"),
+    algorithm!(width=16 poly=0x1021 init=0x0000 refin=false refout=false xorout=0x0000 check=0x31c3 residue=0x0000
+               name="CRC-16/XMODEM" alias="CRC-16/ACORN" alias="CRC-16/LTE" alias="CRC-16/V-41-MSB" alias="XMODEM"
+               alias="ZMODEM"),
+];
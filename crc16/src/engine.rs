@@ -0,0 +1,109 @@
+// The Rocksoft model of a CRC
+//
+// Ross Williams, "A Painless Guide to CRC Error Detection Algorithms" (1993)
+//    WIDTH  -- bits of the CRC, 1 to 16
+//    POLY   -- the generator polynomial, without the x^WIDTH term
+//    INIT   -- the register before the message
+//    REFIN  -- the bytes of the message are input lsb first
+//    REFOUT -- the register is reflected before XOROUT
+//    XOROUT -- xored into the register at the end
+//
+// each algorithm is a type, its table is computed at compile time
+//
+// type Modbus = Crc<16, 0x8005, 0xffff, true, true, 0x0000>;
+// assert_eq!(0x4b37, Modbus::checksum(b"123456789"));
+
+pub struct Crc<
+    const WIDTH: u32,
+    const POLY: u16,
+    const INIT: u16,
+    const REFIN: bool,
+    const REFOUT: bool,
+    const XOROUT: u16,
+>;
+
+impl<const WIDTH: u32, const POLY: u16, const INIT: u16, const REFIN: bool, const REFOUT: bool, const XOROUT: u16>
+    Crc<WIDTH, POLY, INIT, REFIN, REFOUT, XOROUT>
+{
+    pub const TABLE: [u16; 256] = crc_table(WIDTH, POLY, REFIN);
+
+    // The register before the message
+    //
+    // a reflected register is the low WIDTH bits, reflected, otherwise the high WIDTH bits
+    pub const fn init() -> u16 {
+        match REFIN {
+            true => reflect(INIT, WIDTH),
+            false => INIT << (16 - WIDTH),
+        }
+    }
+
+    // The register after `msg`, messages may be input in parts
+    pub fn update(mut crc: u16, msg: &[u8]) -> u16 {
+        for byte in msg.iter() {
+            crc = match REFIN {
+                true => (crc >> 8) ^ Self::TABLE[((crc ^ *byte as u16) & 0xff) as usize],
+                false => (crc << 8) ^ Self::TABLE[((crc >> 8) ^ *byte as u16) as usize],
+            };
+        }
+        crc
+    }
+
+    // The CRC of the register
+    pub const fn finalize(crc: u16) -> u16 {
+        let crc = match REFIN {
+            true => crc,
+            false => crc >> (16 - WIDTH),
+        };
+        let crc = match REFIN != REFOUT {
+            true => reflect(crc, WIDTH),
+            false => crc,
+        };
+        crc ^ XOROUT
+    }
+
+    pub fn checksum(msg: &[u8]) -> u16 {
+        Self::finalize(Self::update(Self::init(), msg))
+    }
+}
+
+// The low `width` bits of `value` in reverse order
+pub const fn reflect(value: u16, width: u32) -> u16 {
+    value.reverse_bits() >> (16 - width)
+}
+
+// Returns the table of a CRC, the register of each byte
+pub const fn crc_table(width: u32, poly: u16, refin: bool) -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < table.len() {
+        table[i] = crc_byte(i as u8, width, poly, refin);
+        i += 1;
+    }
+    table
+}
+
+// A byte shifted through the register, msb first in the high bits,
+// or lsb first in the low bits with the polynomial reflected
+const fn crc_byte(byte: u8, width: u32, poly: u16, refin: bool) -> u16 {
+    let mut i = 0;
+    match refin {
+        true => {
+            let poly = reflect(poly, width);
+            let mut value = byte as u16;
+            while i < 8 {
+                value = (value >> 1) ^ ((value & 1) * poly);
+                i += 1;
+            }
+            value
+        }
+        false => {
+            let poly = poly << (16 - width);
+            let mut value = (byte as u16) << 8;
+            while i < 8 {
+                value = (value << 1) ^ (((value >> 15) & 1) * poly);
+                i += 1;
+            }
+            value
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use anyhow::{Context, Result};
+use catalogue::{Algorithm, CATALOGUE};
 use clap::Parser;
 use std::error::Error;
 use std::fs::File;
 use std::io::{self, Read};
 
-// CRC-16, CRC-16/UMTS
+mod catalogue;
+mod engine;
+
+// CRC-16, CRC-16/UMTS, the default algorithm
 //
 // https://reveng.sourceforge.io/crc-catalogue/all.htm
 // width=16 poly=0x8005
@@ -42,28 +46,6 @@ const TABLE: [u16; 256] = [
       544, 33317, 33327,   554, 33339,   574,   564, 33329, 33299,   534,   540, 33305,   520, 33293, 33287,   514,
 ];
 
-// Returns the CRC-16 TABLE
-const fn crc16_table() -> [u16; 256] {
-    let mut table = [0u16; 256];
-    let mut i = 0;
-    while i < table.len() {
-        table[i] = _crc16_byte(i as u8);
-        i += 1;
-    }
-    table
-}
-
-// TABLE generator using 2 const functions
-const fn _crc16_byte(byte: u8) -> u16 {
-    let mut value: u16 = (byte as u16) << 8;
-    let mut i = 0;
-    while i < 8 {
-        value = (value << 1) ^ (((value >> 15) & 1) * (CRCPOLY >> 16) as u16);
-        i += 1;
-    }
-    value
-}
-
 // TABLE generator (basic)
 #[allow(dead_code)]
 fn crc16_table_generator() -> Vec<u16> {
@@ -100,21 +82,9 @@ fn crc16_algo(msg: &[u8]) -> u16 {
     ((crc >> 16) & 0x0000ffff) as u16
 }
 
-// The algorithm using a table lookup
-fn crc16(msg: &[u8]) -> u16 {
-    // Message must have length > 0
-    assert!(!msg.is_empty(), "Invalid input");
-
-    // Get the table from our const function
-    let table = crc16_table();
-    //let table = TABLE;
-
-    let mut crc = 0u16;
-    for byte in msg.iter() {
-        crc ^= (*byte as u16) << 8;
-        crc = (crc << 8) ^ table[(crc >> 8) as usize];
-    }
-    crc
+// Parse an --algorithm name
+fn parse_algorithm(name: &str) -> Result<&'static Algorithm, String> {
+    Algorithm::find(name).ok_or_else(|| format!("unknown algorithm `{name}`, see --list"))
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -123,9 +93,24 @@ fn main() -> Result<(), Box<dyn Error>> {
     struct Args {
         /// file|stdin, filename of "-" implies stdin
         files: Vec<std::path::PathBuf>,
+
+        /// CRC-16 algorithm, a name of the catalogue (case insensitive)
+        #[clap(short, long, default_value = "CRC-16/UMTS", value_parser = parse_algorithm)]
+        algorithm: &'static Algorithm,
+
+        /// Print the catalogue
+        #[clap(short, long)]
+        list: bool,
     }
     let args = Args::parse();
 
+    if args.list {
+        for algorithm in CATALOGUE.iter() {
+            println!("{algorithm}");
+        }
+        return Ok(());
+    }
+
     let files = match args.files.is_empty() {
         true => vec![std::path::PathBuf::from("-")],
         false => args.files,
@@ -150,9 +135,8 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
         };
 
-        // Output CRC-16/UMTS
-        println!("{input_name}: {}", crc16(&msg));
-        //println!("table = {:?}", crc16_table());
+        // Output the CRC, CRC-16/UMTS by default
+        println!("{input_name}: {}", args.algorithm.checksum(&msg));
     }
 
     Ok(())
@@ -162,6 +146,10 @@ fn main() -> Result<(), Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use engine::{reflect, Crc};
+
+    // The algorithm using a table lookup
+    type Crc16 = Crc<16, 0x8005, 0x0000, false, false, 0x0000>;
 
     const CRC_16_ARC               : crc::Crc<u16> = crc::Crc::<u16>::new(&crc::CRC_16_ARC);
     const CRC_16_CDMA2000          : crc::Crc<u16> = crc::Crc::<u16>::new(&crc::CRC_16_CDMA2000);
@@ -200,15 +188,23 @@ mod tests {
         crc16_algo(&[]);
     }
 
+    // An empty message is valid input (crc16 used to panic on it, as crc16_algo still does),
+    // its CRC is INIT ^ XOROUT, INIT is reflected with REFOUT
     #[test]
-    #[should_panic]
-    fn test_bad_input_2() {
-        crc16(&[]);
+    fn test_empty_input() {
+        assert_eq!(0, Crc16::checksum(&[]));
+        for algorithm in CATALOGUE.iter() {
+            let init = match algorithm.refout {
+                true => reflect(algorithm.init, algorithm.width),
+                false => algorithm.init,
+            };
+            assert_eq!(init ^ algorithm.xorout, algorithm.checksum(&[]), "{}", algorithm.name);
+        }
     }
 
     #[test]
     fn table_test() {
-        let table = crc16_table();
+        let table = Crc16::TABLE;
         assert_eq!(TABLE, table);
 
         for (i, val) in crc16_table_generator().into_iter().enumerate() {
@@ -216,20 +212,20 @@ mod tests {
             assert_eq!(table[i], val);
         }
 
-        for i in 0..256 {
+        for (i, val) in TABLE.iter().enumerate() {
             let data = [i as u8];
-            assert_eq!(TABLE[i], crc16(&data));
-            assert_eq!(TABLE[i], crc16_algo(&data));
-            assert_eq!(TABLE[i], CRC_16_UMTS.checksum(&data));
+            assert_eq!(*val, Crc16::checksum(&data));
+            assert_eq!(*val, crc16_algo(&data));
+            assert_eq!(*val, CRC_16_UMTS.checksum(&data));
         }
     }
 
     #[test]
     fn test1() {
         let data = [5, 0, 255, 255, 255, 255, 0, 0, 0, 0, 2, 0, 1, 1, 0, 0];
-        assert_eq!(35273, crc16(&data));
-        assert_eq!(crc16(&data), crc16_algo(&data));
-        assert_eq!(crc16(&data), CRC_16_UMTS.checksum(&data));
+        assert_eq!(35273, Crc16::checksum(&data));
+        assert_eq!(Crc16::checksum(&data), crc16_algo(&data));
+        assert_eq!(Crc16::checksum(&data), CRC_16_UMTS.checksum(&data));
     }
 
     #[test]
@@ -237,33 +233,33 @@ mod tests {
         let data = [
             170, 170, 170, 170, 170, 170, 170, 170, 204, 204, 204, 204, 204, 204, 204, 204,
         ];
-        assert_eq!(43036, crc16(&data));
-        assert_eq!(crc16(&data), crc16_algo(&data));
-        assert_eq!(crc16(&data), CRC_16_UMTS.checksum(&data));
+        assert_eq!(43036, Crc16::checksum(&data));
+        assert_eq!(Crc16::checksum(&data), crc16_algo(&data));
+        assert_eq!(Crc16::checksum(&data), CRC_16_UMTS.checksum(&data));
     }
 
     #[test]
     fn test3() {
         let data = [100, 97, 118, 101];
-        assert_eq!(25309, crc16(&data));
-        assert_eq!(crc16(&data), crc16_algo(&data));
-        assert_eq!(crc16(&data), CRC_16_UMTS.checksum(&data));
+        assert_eq!(25309, Crc16::checksum(&data));
+        assert_eq!(Crc16::checksum(&data), crc16_algo(&data));
+        assert_eq!(Crc16::checksum(&data), CRC_16_UMTS.checksum(&data));
 
-        assert_eq!(25309, crc16(b"dave"));
-        assert_eq!(crc16(b"dave"), crc16_algo(b"dave"));
-        assert_eq!(crc16(b"dave"), CRC_16_UMTS.checksum(b"dave"));
+        assert_eq!(25309, Crc16::checksum(b"dave"));
+        assert_eq!(Crc16::checksum(b"dave"), crc16_algo(b"dave"));
+        assert_eq!(Crc16::checksum(b"dave"), CRC_16_UMTS.checksum(b"dave"));
     }
 
     #[test]
     fn test4() {
         let data = [49, 50, 51, 52, 53, 54, 55, 56, 57];
-        assert_eq!(65256, crc16(&data));
-        assert_eq!(crc16(&data), crc16_algo(&data));
-        assert_eq!(crc16(&data), CRC_16_UMTS.checksum(&data));
+        assert_eq!(65256, Crc16::checksum(&data));
+        assert_eq!(Crc16::checksum(&data), crc16_algo(&data));
+        assert_eq!(Crc16::checksum(&data), CRC_16_UMTS.checksum(&data));
 
-        assert_eq!(65256, crc16(b"123456789"));
-        assert_eq!(crc16(b"123456789"), crc16_algo(b"123456789"));
-        assert_eq!(crc16(b"123456789"), CRC_16_UMTS.checksum(b"123456789"));
+        assert_eq!(65256, Crc16::checksum(b"123456789"));
+        assert_eq!(Crc16::checksum(b"123456789"), crc16_algo(b"123456789"));
+        assert_eq!(Crc16::checksum(b"123456789"), CRC_16_UMTS.checksum(b"123456789"));
     }
 
     #[test]
@@ -300,4 +296,112 @@ mod tests {
         assert_eq!(46280 , CRC_16_USB.checksum(data));
         assert_eq!(12739 , CRC_16_XMODEM.checksum(data));
     }
+
+    // Every algorithm against the crc crate
+    #[test]
+    fn catalogue_test() {
+        let crates = [
+            ("CRC-16/ARC", CRC_16_ARC),
+            ("CRC-16/CDMA2000", CRC_16_CDMA2000),
+            ("CRC-16/CMS", CRC_16_CMS),
+            ("CRC-16/DDS-110", CRC_16_DDS_110),
+            ("CRC-16/DECT-R", CRC_16_DECT_R),
+            ("CRC-16/DECT-X", CRC_16_DECT_X),
+            ("CRC-16/DNP", CRC_16_DNP),
+            ("CRC-16/EN-13757", CRC_16_EN_13757),
+            ("CRC-16/GENIBUS", CRC_16_GENIBUS),
+            ("CRC-16/GSM", CRC_16_GSM),
+            ("CRC-16/IBM-3740", CRC_16_IBM_3740),
+            ("CRC-16/IBM-SDLC", CRC_16_IBM_SDLC),
+            ("CRC-16/ISO-IEC-14443-3-A", CRC_16_ISO_IEC_14443_3_A),
+            ("CRC-16/KERMIT", CRC_16_KERMIT),
+            ("CRC-16/LJ1200", CRC_16_LJ1200),
+            ("CRC-16/MAXIM-DOW", CRC_16_MAXIM_DOW),
+            ("CRC-16/MCRF4XX", CRC_16_MCRF4XX),
+            ("CRC-16/MODBUS", CRC_16_MODBUS),
+            ("CRC-16/NRSC-5", CRC_16_NRSC_5),
+            ("CRC-16/OPENSAFETY-A", CRC_16_OPENSAFETY_A),
+            ("CRC-16/OPENSAFETY-B", CRC_16_OPENSAFETY_B),
+            ("CRC-16/PROFIBUS", CRC_16_PROFIBUS),
+            ("CRC-16/RIELLO", CRC_16_RIELLO),
+            ("CRC-16/SPI-FUJITSU", CRC_16_SPI_FUJITSU),
+            ("CRC-16/T10-DIF", CRC_16_T10_DIF),
+            ("CRC-16/TELEDISK", CRC_16_TELEDISK),
+            ("CRC-16/TMS37157", CRC_16_TMS37157),
+            ("CRC-16/UMTS", CRC_16_UMTS),
+            ("CRC-16/USB", CRC_16_USB),
+            ("CRC-16/XMODEM", CRC_16_XMODEM),
+        ];
+        let data: Vec<u8> = (0..=255).collect();
+        for (name, crc) in crates {
+            let algorithm = Algorithm::find(name).unwrap();
+            let expected = crc.algorithm;
+            assert_eq!(expected.width as u32, algorithm.width, "{name}");
+            assert_eq!(expected.poly,    algorithm.poly,    "{name}");
+            assert_eq!(expected.init,    algorithm.init,    "{name}");
+            assert_eq!(expected.refin,   algorithm.refin,   "{name}");
+            assert_eq!(expected.refout,  algorithm.refout,  "{name}");
+            assert_eq!(expected.xorout,  algorithm.xorout,  "{name}");
+            assert_eq!(expected.check,   algorithm.check,   "{name}");
+            assert_eq!(expected.residue, algorithm.residue, "{name}");
+            assert_eq!(crc.checksum(&data), algorithm.checksum(&data), "{name}");
+            assert_eq!(crc.checksum(&[]), algorithm.checksum(&[]), "{name}");
+        }
+    }
+
+    // The check and residue of every algorithm
+    #[test]
+    fn catalogue_check_residue() {
+        for algorithm in CATALOGUE.iter() {
+            let name = algorithm.name;
+            assert_eq!(algorithm.check, algorithm.checksum(b"123456789"), "{name}");
+
+            // A message and its CRC, lsb first when reflected
+            let mut codeword = b"123456789".to_vec();
+            match algorithm.refout {
+                true => codeword.extend(algorithm.check.to_le_bytes()),
+                false => codeword.extend(algorithm.check.to_be_bytes()),
+            }
+            let residue = algorithm.checksum(&codeword) ^ algorithm.xorout;
+            assert_eq!(algorithm.residue, residue, "{name}");
+        }
+    }
+
+    #[test]
+    fn catalogue_names() {
+        assert_eq!(CATALOGUE.len(), 31);
+        for (i, algorithm) in CATALOGUE.iter().enumerate() {
+            assert!(std::ptr::eq(algorithm, Algorithm::find(algorithm.name).unwrap()));
+            assert!(std::ptr::eq(algorithm, Algorithm::find(&algorithm.name.to_lowercase()).unwrap()));
+            for alias in algorithm.aliases {
+                assert!(std::ptr::eq(algorithm, Algorithm::find(alias).unwrap()), "{alias}");
+            }
+            if i > 0 {
+                assert!(CATALOGUE[i - 1].name < algorithm.name);
+            }
+        }
+        assert_eq!("CRC-16/MODBUS", Algorithm::find("modbus").unwrap().name);
+        assert_eq!("CRC-16/IBM-3740", Algorithm::find("CRC-16/CCITT-FALSE").unwrap().name);
+        assert!(Algorithm::find("CRC-32").is_none());
+        assert_eq!(
+            "width=16 poly=0x8005 init=0xffff refin=true refout=true xorout=0x0000 check=0x4b37 residue=0x0000 \
+             name=\"CRC-16/MODBUS\" alias=\"MODBUS\"",
+            Algorithm::find("CRC-16/MODBUS").unwrap().to_string()
+        );
+    }
+
+    // A message input in parts, and widths under 16
+    #[test]
+    fn engine_test() {
+        type Modbus = Crc<16, 0x8005, 0xffff, true, true, 0x0000>;
+        let crc = Modbus::update(Modbus::init(), b"1234");
+        assert_eq!(0x4b37, Modbus::finalize(Modbus::update(crc, b"56789")));
+
+        // CRC-15/CAN, CRC-12/UMTS, CRC-8/MAXIM-DOW, CRC-5/USB, CRC-3/ROHC
+        assert_eq!(0x059e, Crc::<15, 0x4599, 0x0000, false, false, 0x0000>::checksum(b"123456789"));
+        assert_eq!(0x0daf, Crc::<12, 0x080f, 0x0000, false, true, 0x0000>::checksum(b"123456789"));
+        assert_eq!(0x00a1, Crc::<8, 0x31, 0x00, true, true, 0x00>::checksum(b"123456789"));
+        assert_eq!(0x0019, Crc::<5, 0x05, 0x1f, true, true, 0x1f>::checksum(b"123456789"));
+        assert_eq!(0x0006, Crc::<3, 0x3, 0x7, true, true, 0x0>::checksum(b"123456789"));
+    }
 }
@@ -12,16 +12,17 @@ pub fn get_args() -> ArgMatches {
         .max_term_width(100)
         .arg(
             Arg::new("FILE")
-                .help("File to read, use '-' for standard input")
-                .value_parser(value_parser!(PathBuf)),
+                .help("Files to read, use '-' for standard input")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Append),
         )
         .arg(
             Arg::new("fields")
                 .short('f')
                 .value_name("field_spec")
-                .help("[-]number, range, or regex (use `--help` for more detail)")
+                .help("[-]number, range, regex, or header name (use `--help` for more detail)")
                 .long_help(
-                    "[-]number, range, or regex\n\
+                    "[-]number, range, regex, or header name\n\
                         \n\
                         <field_spec> syntax:\n\
                         -f N          # position index starting at 1\n\
@@ -30,6 +31,8 @@ pub fn get_args() -> ArgMatches {
                         -f N-         # position open range to the end\n\
                         -f r/REGEX/   # regex match over fields on the \"file header\"\n\
                         -f R/REGEX/   # regex match over fields on all input\n\
+                        -f NAME       # header field named NAME (see -i)\n\
+                        -f NAME-NAME  # range between header names\n\
                         -f <field_spec>[,<field_spec>,...]
                         \n\
                         Each <field_spec> is represented by one or more enumerations\n\
@@ -37,6 +40,7 @@ pub fn get_args() -> ArgMatches {
                         \tFieldSpec::OpenRange(a)\n\
                         \tFieldSpec::ClosedRange(a, b)\n\
                         \tFieldSpec::Last(a)\n\
+                        \tFieldSpec::Name(a)\n\
                         \tFieldSpec::RegularExpression(re)\n\
                         \n\
                         The combinded list of enumerations operate over the tokenized input\n\
@@ -54,24 +58,29 @@ pub fn get_args() -> ArgMatches {
                         * -f-2        # second to last index\n\
                         * -f'r/^.{3}$/' # index of fields with exactly 3 characters in \"file header\"\n\
                         * -f'R/^.{3}$/' # index of fields with exactly 3 characters (matched against all data)\n\
+                        * -fname,email,-1 # header fields \"name\", \"email\", and the last index\n\
+                        * -f'name-zip'  # header fields from \"name\" through \"zip\"\n\
                         \n\
                         More Information\n\
                         -f-N must be specified without spaces; use -f-2 not -f -2\n\
                         \n\
-                        -fr, -fR, can optionally specify the pattern between slashes (/) as -fr//, -fR//\n\
+                        Header names are exact matches over the first line (-i ignores case),\n\
+                        a name which is not in the header is an error (e.g. -f rate)\n\
+                        \n\
+                        -fr, -fR, specify the pattern between slashes (/) as -fr/REGEX/, -fR/REGEX/\n\
                         \n\
                         When using -f[rR] in a list, comma (,) is treated as a <field_spec> separator not a\n\
                         component of the Regular Expression.\n\
                         Isolate the REGEX into a separate -f[rR]// to avoid <filed_spec> list splitting
                         \n\
                         Ex: select the first field, header fields beginning with \"foo\", and the last field\n\
-                        \t-f1,r^foo,-1\n\
+                        \t-f1,r/^foo/,-1\n\
                         \t\tor\n\
                         \t-f1 -fr/^foo/ -f-1",
                 )
                 .value_parser(clap::builder::NonEmptyStringValueParser::new())
                 .action(ArgAction::Append)
-                .required(true),
+                .required_unless_present_any(["dump_spec", "format", "chars", "bytes"]),
         )
         .arg(
             Arg::new("input_delim")
@@ -92,11 +101,94 @@ pub fn get_args() -> ArgMatches {
                 .action(clap::ArgAction::SetTrue)
                 .help("Short for -d'\\t'"),
         )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .action(clap::ArgAction::SetTrue)
+                .help("Parse RFC 4180 CSV with -d as the delimiter (default ','), output fields are re-quoted"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["input_delim", "tab", "csv", "widths", "chars", "bytes", "header", "no_header"])
+                .help("Read a JSON object per line, -f selects keys, dotted paths, or JSON pointers (use `--help` for more detail)")
+                .long_help(
+                    "Read a JSON object per line (JSON Lines, NDJSON), blank lines are skipped\n\
+                        \n\
+                        The fields of a line are the values of the top-level keys of the first object,\n\
+                        in order, then the values of the -f paths which are not a key. Strings are output\n\
+                        without quotes, other values as JSON. The keys and paths are the header names for\n\
+                        -f, -fr, --format, --where, and --output json.\n\
+                        \n\
+                        -f user.id        # dotted path, an array element by index e.g. events.0.ts\n\
+                        -f /events/0/ts   # JSON pointer\n\
+                        -f 2, -f name     # the 2nd key, the key \"name\"\n\
+                        \n\
+                        See --missing for a selected path which is not in an object.\n\
+                        \n\
+                        Example: cutr --json -f ts,user.id,/events/0/type -o,",
+                ),
+        )
+        .arg(
+            Arg::new("missing")
+                .long("missing")
+                .value_name("policy")
+                .value_parser(["empty", "null", "skip", "error"])
+                .requires("json")
+                .help("A selected --json field missing from an object is empty (default), null, skips the line, or is an error"),
+        )
+        .arg(
+            Arg::new("widths")
+                .long("widths")
+                .value_name("list")
+                .conflicts_with_all(["input_delim", "tab", "csv"])
+                .help("Split lines into fixed-width columns of <list> chars, e.g. 10,5,20 (use -t to trim padding)")
+                .long_help(
+                    "Split lines into fixed-width columns, <list> is the comma separated column widths\n\
+                        counted in chars (graphemes). The columns are the fields selected by -f, --format,\n\
+                        and --where. Text past the last column is one more field, columns past the end\n\
+                        of a short line are empty. Use -t to trim the column padding.\n\
+                        \n\
+                        Example: the 2nd and 3rd columns of 10, 5, and 20 char columns\n\
+                        \t--widths 10,5,20 -f2-3 -t",
+                ),
+        )
+        .arg(
+            Arg::new("chars")
+                .long("chars")
+                .value_name("list")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .action(ArgAction::Append)
+                .conflicts_with_all(["fields", "format", "input_delim", "tab", "csv", "widths", "bytes"])
+                .help("Select chars (graphemes) by -f <field_spec> positions, e.g. --chars 1-10,-1")
+                .long_help(
+                    "Select chars by position using the -f <field_spec> positions (N, N-M, N-, -N) only,\n\
+                        a char is a user-perceived character (an extended grapheme cluster), e.g. an 'e'\n\
+                        with a combining accent is one char. The selected chars are output without a\n\
+                        separator unless -o.\n\
+                        \n\
+                        Examples\n\
+                        * --chars 1-10     # the first 10 chars\n\
+                        * --chars 5-,-1    # chars 5 to the end, then the last char again\n\
+                        * --chars 3-1      # the first 3 chars in reverse order",
+                ),
+        )
+        .arg(
+            Arg::new("bytes")
+                .short('b')
+                .long("bytes")
+                .value_name("list")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .action(ArgAction::Append)
+                .conflicts_with_all(["fields", "format", "input_delim", "tab", "csv", "widths"])
+                .help("Select bytes by -f <field_spec> positions, e.g. -b 1-10, output as --chars (may split a char)"),
+        )
         .arg(
             Arg::new("output_delim")
                 .short('o')
                 .value_name("str")
-                .help("Use <str> as the output field separator, default is to use -d, or '\\t'"),
+                .help("Use <str> as the output field separator, default is to use -d, none for --chars/-b, or '\\t'"),
         )
         .arg(
             Arg::new("sorted")
@@ -137,6 +229,163 @@ pub fn get_args() -> ArgMatches {
                 .short('z')
                 .action(clap::ArgAction::SetTrue)
                 .help("Don't output empty lines"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("template")
+                .conflicts_with("fields")
+                .help("Output lines rendered from <template> (use `--help` for more detail)")
+                .long_help(
+                    "Output lines rendered from <template> instead of joined -f fields\n\
+                        \n\
+                        {N}, {-N}, {NAME} are replaced by a field: an index, an index from the end,\n\
+                        or a header name (see -i), fields out of range are empty.\n\
+                        Use {{ and }} for literal braces.\n\
+                        \n\
+                        Transforms follow the field separated by |\n\
+                        \tupper, lower, trim\n\
+                        \tsubstr:START[:LEN]   # chars from START (0-based)\n\
+                        \tpad:WIDTH[:FILL]     # pad on the right to WIDTH chars\n\
+                        \tlpad:WIDTH[:FILL]    # pad on the left to WIDTH chars\n\
+                        \treplace:/RE/REPL/    # replace all RE matches, any char may replace /\n\
+                        \n\
+                        Examples\n\
+                        * --format '{3} ({1})'\n\
+                        * --format '{name|upper}: {-1|trim|lpad:8:0}'\n\
+                        * --format '{2|replace:#(\\d+)-(\\d+)#$2-$1#}'",
+                ),
+        )
+        .arg(
+            Arg::new("where")
+                .long("where")
+                .value_name("predicate")
+                .help("Output only lines matching <predicate> (use `--help` for more detail)")
+                .long_help(
+                    "Output only lines matching <predicate>, tested before field extraction\n\
+                        \n\
+                        <predicate> compares fields, N, -N, or header names (see -i), to values\n\
+                        \tFIELD ~ /REGEX/    FIELD !~ /REGEX/\n\
+                        \tFIELD == VALUE     FIELD != VALUE\n\
+                        \tFIELD < VALUE      FIELD <= VALUE     FIELD > VALUE     FIELD >= VALUE\n\
+                        combined with and (&&), or (||), not (!), and parentheses.\n\
+                        VALUE is a word or a \"quoted string\", compared as numbers when both\n\
+                        sides are finite numbers, otherwise as strings (nan and inf are strings).\n\
+                        \n\
+                        Line 1 is tested like any other line, --header always outputs it.\n\
+                        \n\
+                        Examples\n\
+                        * --where '3 ~ /ERROR/'\n\
+                        * --where 'status == 200'\n\
+                        * --where 'size >= 1024 and (user == root or -1 ~ /denied/)'",
+                ),
+        )
+        .arg(
+            Arg::new("invert")
+                .long("invert")
+                .action(clap::ArgAction::SetTrue)
+                .requires("where")
+                .help("Output only lines not matching the --where predicate"),
+        )
+        .arg(
+            Arg::new("ignore_case")
+                .short('i')
+                .action(clap::ArgAction::SetTrue)
+                .help("Match -f header names ignoring case"),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .action(clap::ArgAction::SetTrue)
+                .overrides_with("no_header")
+                .help("Output the fields of line 1 (the header) even when --where does not match it"),
+        )
+        .arg(
+            Arg::new("no_header")
+                .long("no-header")
+                .action(clap::ArgAction::SetTrue)
+                .overrides_with("header")
+                .help("Skip line 1 (the header) on output, it still resolves -f names"),
+        )
+        .arg(
+            Arg::new("per_file_header")
+                .long("per-file-header")
+                .action(clap::ArgAction::SetTrue)
+                .help("Line 1 of every file is a header, resolving -f names, -fr, --format, and --where per file"),
+        )
+        .arg(
+            Arg::new("with_filename")
+                .short('H')
+                .action(clap::ArgAction::SetTrue)
+                .help("Add a beginning field on output denoting the filename of the input"),
+        )
+        .arg(
+            Arg::new("null_data")
+                .long("null-data")
+                .action(clap::ArgAction::SetTrue)
+                .help("Input and output records are NUL terminated instead of newline, e.g. for find -print0"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("format")
+                .default_value("text")
+                .value_parser(["text", "csv", "json", "ndjson"])
+                .conflicts_with("format")
+                .help("Output the fields as text, csv, a json array of objects, or ndjson objects per line")
+                .long_help(
+                    "Output the selected fields as\n\
+                        \ttext    # joined by the output delimiter (-o)\n\
+                        \tcsv     # RFC 4180 quoted, joined by ',' or -o\n\
+                        \tjson    # one array of objects, an object per line\n\
+                        \tndjson  # an object per line\n\
+                        \n\
+                        Objects are keyed by the header names with --no-header, otherwise by field\n\
+                        index (\"1\", \"2\", ...), after \"file\" (-H) and \"line\" (-n) keys. A repeated key\n\
+                        is given a suffix, e.g. -f 1,1 is keyed \"1\" and \"1_2\".\n\
+                        With --json the values are output as they were read, numbers, arrays, and\n\
+                        objects included, a missing value is null with --missing null, otherwise \"\".\n\
+                        \n\
+                        Example: cutr --csv --no-header -n -f name,age --output ndjson\n\
+                        \t{\"line\":2,\"name\":\"ann\",\"age\":\"3\"}",
+                ),
+        )
+        .arg(
+            Arg::new("stopwords")
+                .long("stopwords")
+                .value_name("list")
+                .help("Discard tokens in <list>: a builtin (english, lucene), @file, or comma separated words"),
+        )
+        .arg(
+            Arg::new("keepwords")
+                .long("keepwords")
+                .value_name("list")
+                .help("Keep only tokens in <list>: a builtin (english, lucene), @file, or comma separated words"),
+        )
+        .arg(
+            Arg::new("encoding")
+                .long("encoding")
+                .value_name("mode")
+                .default_value("strict")
+                .value_parser(["strict", "lossy", "bytes"])
+                .help("Invalid UTF-8 input: error (strict), replace with U+FFFD (lossy), or output the original bytes (bytes)"),
+        )
+        .arg(
+            Arg::new("spec")
+                .long("spec")
+                .value_name("file")
+                .value_parser(value_parser!(PathBuf))
+                .help("Load a TokenizationSpec (JSON or TOML) for field parsing, -d -T -t override it"),
+        )
+        .arg(
+            Arg::new("dump_spec")
+                .long("dump-spec")
+                .value_name("format")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("json")
+                .value_parser(["json", "toml"])
+                .help("Print the field parsing TokenizationSpec in <format> (default json) and exit"),
         );
     app.get_matches_from(env::args().collect::<Vec<String>>())
 }
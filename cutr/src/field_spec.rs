@@ -8,16 +8,58 @@ use crate::Regex;
 // "-f-1"               =>  FieldSpec::Last(1)
 // "-fr." or "-f r.     =>  computed indices on Regex header matches into => List(FieldSpec::Index)
 // "-fR." or "-f R.     =>  FieldSpec::RegularExpression(re), computed indices on Regex data matches into => List(FieldSpec::Index)
+// "-f name"            =>  FieldSpec::Name(2), resolved to the header position of "name"
+// "-f name-zip"        =>  FieldSpec::ClosedRange(2, 5), the header positions of "name" and "zip"
 #[derive(Debug)]
 pub enum FieldSpec {
     Index(usize),
+    Name(usize),
     Last(usize),
     OpenRange(usize),
     ClosedRange(usize, usize),
     RegularExpression(Regex),
 }
 impl FieldSpec {
-    pub fn indices(&self, tokens: &[String]) -> Vec<usize> {
+    // a single field: N, -N, or a header name
+    // returns None when `s` is not an index and does not name a header field
+    pub fn field<S: AsRef<str>>(s: &str, header: &[S], ignore_case: bool) -> Option<Self> {
+        match s.strip_prefix('-').unwrap_or(s).parse::<usize>() {
+            Ok(n) if s.starts_with('-') => Some(FieldSpec::Last(n)),
+            Ok(n) => Some(FieldSpec::Index(n)),
+            Err(_) => match Self::from_name(s, header, ignore_case) {
+                Some(FieldSpec::Name(i)) => Some(FieldSpec::Name(i)),
+                _ => None,
+            },
+        }
+    }
+
+    // the text of a single field, empty when out of range
+    pub fn text<'a, S: AsRef<str>>(&self, tokens: &'a [S]) -> &'a str {
+        self.indices(tokens).first().map_or("", |i| tokens[*i].as_ref())
+    }
+
+    // resolve a header name or a "first-last" name range against the `header` fields
+    // returns None when `s` does not name header fields
+    pub fn from_name<S: AsRef<str>>(s: &str, header: &[S], ignore_case: bool) -> Option<Self> {
+        let position = |name: &str| {
+            header
+                .iter()
+                .position(|h| match ignore_case {
+                    true => h.as_ref().to_lowercase() == name.to_lowercase(),
+                    false => h.as_ref() == name,
+                })
+                .map(|i| i + 1)
+        };
+        match position(s) {
+            Some(i) => Some(FieldSpec::Name(i)),
+            // names may contain '-', try every split
+            None => s
+                .match_indices('-')
+                .find_map(|(d, _)| Some(FieldSpec::ClosedRange(position(&s[..d])?, position(&s[d + 1..])?))),
+        }
+    }
+
+    pub fn indices<S: AsRef<str>>(&self, tokens: &[S]) -> Vec<usize> {
         let indices = |start: usize, end: usize| -> Vec<usize> {
             (match start <= end {
                 true => (start..=end).collect::<Vec<_>>(),
@@ -29,14 +71,17 @@ impl FieldSpec {
             .collect()
         };
         match self {
-            FieldSpec::Index(a) => indices(*a, *a),
+            FieldSpec::Index(a) | FieldSpec::Name(a) => indices(*a, *a),
             FieldSpec::OpenRange(a) => indices(*a, tokens.len()),
             FieldSpec::ClosedRange(a, b) => indices(*a, *b),
-            FieldSpec::Last(a) => indices(tokens.len() + 1 - *a, tokens.len() + 1 - *a),
+            FieldSpec::Last(a) => indices(
+                (tokens.len() + 1).saturating_sub(*a),
+                (tokens.len() + 1).saturating_sub(*a),
+            ),
             FieldSpec::RegularExpression(re) => tokens
                 .iter()
                 .enumerate()
-                .filter(|(_, txt)| re.is_match(txt))
+                .filter(|(_, txt)| re.is_match(txt.as_ref()))
                 .flat_map(|(i, _)| indices(i + 1, i + 1))
                 .collect(),
         }
@@ -0,0 +1,292 @@
+use crate::field_spec::FieldSpec;
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+
+// A --format Template renders an output line from the fields of an input line
+//
+// "{3} ({1})"                  =>  field 3, then field 1 in parentheses
+// "{name|upper}"               =>  header field "name" upper-cased
+// "{-1|trim|pad:8}"            =>  last field trimmed, padded to 8 chars
+// "{2|replace:/(\d+)-/$1:/}"   =>  regex replace_all over field 2
+// "{{" and "}}"                =>  literal braces
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug)]
+enum Part {
+    Literal(String),
+    Field(FieldSpec, Vec<Transform>),
+}
+
+#[derive(Debug)]
+enum Transform {
+    Upper,
+    Lower,
+    Trim,
+    // chars from a 0-based start, optional length
+    Substr(usize, Option<usize>),
+    // pad to a width with a fill char, on the right (Pad) or left (LPad)
+    Pad(usize, char),
+    LPad(usize, char),
+    Replace(Regex, String),
+}
+
+impl Template {
+    // parse `template`, header names are resolved against `header`
+    pub fn parse<S: AsRef<str> + std::fmt::Debug>(template: &str, header: &[S], ignore_case: bool) -> Result<Self> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    let (part, rest) = Self::field(chars.as_str(), header, ignore_case)
+                        .with_context(|| format!("--format {template:?}"))?;
+                    parts.push(part);
+                    chars = rest.chars();
+                }
+                '}' => bail!("--format {template:?}: unmatched '}}', use '}}}}' for a literal brace"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    // parse "ref|transform|...}" returning the Part and the text after the '}'
+    fn field<'a, S: AsRef<str> + std::fmt::Debug>(
+        text: &'a str,
+        header: &[S],
+        ignore_case: bool,
+    ) -> Result<(Part, &'a str)> {
+        let end = text.find(['|', '}']).ok_or_else(|| anyhow!("unclosed '{{'"))?;
+        let name = &text[..end];
+        let field = FieldSpec::field(name, header, ignore_case)
+            .ok_or_else(|| anyhow!("{{{name}}} is not an index or a header name in {header:?}"))?;
+
+        let mut transforms = vec![];
+        let mut rest = &text[end..];
+        while let Some(t) = rest.strip_prefix('|') {
+            let (transform, after) = Self::transform(t)?;
+            transforms.push(transform);
+            rest = after;
+        }
+        let rest = rest.strip_prefix('}').ok_or_else(|| anyhow!("unclosed '{{'"))?;
+        Ok((Part::Field(field, transforms), rest))
+    }
+
+    // parse "name[:args]" up to the next '|' or '}'
+    fn transform(text: &str) -> Result<(Transform, &str)> {
+        let end = text.find([':', '|', '}']).ok_or_else(|| anyhow!("unclosed '{{'"))?;
+        let name = &text[..end];
+
+        // replace:/RE/REPLACEMENT/, any char may delimit the RE and REPLACEMENT
+        if name == "replace" {
+            let args = text[end..]
+                .strip_prefix(':')
+                .ok_or_else(|| anyhow!("replace requires :/RE/REPLACEMENT/"))?;
+            let delim = args
+                .chars()
+                .next()
+                .ok_or_else(|| anyhow!("replace requires :/RE/REPLACEMENT/"))?;
+            let mut split = args[delim.len_utf8()..].splitn(3, delim);
+            let (re, replacement, rest) = match (split.next(), split.next(), split.next()) {
+                (Some(re), Some(replacement), Some(rest)) => (re, replacement, rest),
+                _ => bail!("replace requires :{delim}RE{delim}REPLACEMENT{delim}"),
+            };
+            if !rest.starts_with(['|', '}']) {
+                bail!("replace takes :{delim}RE{delim}REPLACEMENT{delim}, got {rest:?} after it");
+            }
+            let re = Regex::new(re).with_context(|| format!("replace {re:?}"))?;
+            return Ok((Transform::Replace(re, replacement.into()), rest));
+        }
+
+        let args_end = text.find(['|', '}']).ok_or_else(|| anyhow!("unclosed '{{'"))?;
+        let args = match end < args_end {
+            true => text[end + 1..args_end].split(':').collect::<Vec<_>>(),
+            false => vec![],
+        };
+        let number = |i: usize| -> Result<Option<usize>> {
+            args.get(i)
+                .map(|n| n.parse::<usize>().with_context(|| format!("{name} {n:?}")))
+                .transpose()
+        };
+        let fill = || -> Result<char> {
+            match args.get(1) {
+                None => Ok(' '),
+                Some(c) if c.chars().count() == 1 => Ok(c.chars().next().expect("one char")),
+                Some(c) => bail!("{name} fill {c:?} is not a single char"),
+            }
+        };
+        let usage = match name {
+            "substr" => ":START[:LEN]",
+            "pad" | "lpad" => ":WIDTH[:FILL]",
+            _ => "",
+        };
+        let transform = match name {
+            "upper" => Transform::Upper,
+            "lower" => Transform::Lower,
+            "trim" => Transform::Trim,
+            "substr" => Transform::Substr(
+                number(0)?.ok_or_else(|| anyhow!("substr requires {usage}"))?,
+                number(1)?,
+            ),
+            "pad" => Transform::Pad(number(0)?.ok_or_else(|| anyhow!("pad requires {usage}"))?, fill()?),
+            "lpad" => Transform::LPad(number(0)?.ok_or_else(|| anyhow!("lpad requires {usage}"))?, fill()?),
+            _ => bail!("unknown transform {name:?}, use upper, lower, trim, substr, pad, lpad, or replace"),
+        };
+        // more arguments than the usage are an error, not ignored
+        if args.len() > usage.matches(':').count() {
+            match usage {
+                "" => bail!("{name} takes no arguments, got {:?}", args.join(":")),
+                _ => bail!("{name} takes {usage}, got {:?}", args.join(":")),
+            }
+        }
+        Ok((transform, &text[args_end..]))
+    }
+
+    // render a line, fields out of range are empty
+    pub fn render<S: AsRef<str>>(&self, tokens: &[S]) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => line.push_str(text),
+                Part::Field(field, transforms) => {
+                    let text = field.text(tokens);
+                    line.push_str(&transforms.iter().fold(text.to_string(), |text, t| t.apply(text)));
+                }
+            }
+        }
+        line
+    }
+}
+
+impl Transform {
+    fn apply(&self, text: String) -> String {
+        let pad = |width: usize, fill: char| fill.to_string().repeat(width.saturating_sub(text.chars().count()));
+        match self {
+            Transform::Upper => text.to_uppercase(),
+            Transform::Lower => text.to_lowercase(),
+            Transform::Trim => text.trim().into(),
+            Transform::Substr(start, len) => text.chars().skip(*start).take(len.unwrap_or(usize::MAX)).collect(),
+            Transform::Pad(width, fill) => {
+                let padding = pad(*width, *fill);
+                text + &padding
+            }
+            Transform::LPad(width, fill) => pad(*width, *fill) + &text,
+            Transform::Replace(re, replacement) => re.replace_all(&text, replacement.as_str()).into_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: [&str; 3] = ["id", "name", "city"];
+
+    fn render(template: &str, tokens: &[&str]) -> String {
+        Template::parse(template, &HEADER, false).unwrap().render(tokens)
+    }
+
+    // the error and its causes on one line
+    fn error(template: &str) -> String {
+        format!("{:#}", Template::parse(template, &HEADER, false).unwrap_err())
+    }
+
+    #[test]
+    fn fields() {
+        let tokens = ["7", "Ada", "London"];
+        assert_eq!(render("{3} ({1})", &tokens), "London (7)");
+        assert_eq!(render("{name}: {-1}", &tokens), "Ada: London");
+        assert_eq!(render("{{{2}}}", &tokens), "{Ada}");
+        assert_eq!(render("[{9}]", &tokens), "[]");
+        assert_eq!(render("no fields", &tokens), "no fields");
+
+        let template = Template::parse("{NAME}", &HEADER, true).unwrap();
+        assert_eq!(template.render(&tokens), "Ada");
+    }
+
+    #[test]
+    fn transforms() {
+        let tokens = ["  Ada Lovelace ", "x-12-y"];
+        assert_eq!(render("{1|upper}", &tokens), "  ADA LOVELACE ");
+        assert_eq!(render("{1|trim|lower}", &tokens), "ada lovelace");
+        assert_eq!(render("{1|trim|substr:4}", &tokens), "Lovelace");
+        assert_eq!(render("{1|trim|substr:0:3}", &tokens), "Ada");
+        assert_eq!(render("{1|trim|substr:0:3|pad:5}|", &tokens), "Ada  |");
+        assert_eq!(render("{1|trim|substr:0:3|pad:5:.}", &tokens), "Ada..");
+        assert_eq!(render("{1|trim|substr:0:3|lpad:5:0}", &tokens), "00Ada");
+        assert_eq!(render("{1|trim|pad:2}", &tokens), "Ada Lovelace");
+        assert_eq!(render("{2|replace:/(\\d+)-/<$1>/}", &tokens), "x-<12>y");
+        assert_eq!(render("{2|replace:#-#:#|upper}", &tokens), "X:12:Y");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("{1"), r#"--format "{1": unclosed '{'"#);
+        assert_eq!(
+            error("1}"),
+            r#"--format "1}": unmatched '}', use '}}' for a literal brace"#
+        );
+        assert_eq!(
+            error("{zip}"),
+            r#"--format "{zip}": {zip} is not an index or a header name in ["id", "name", "city"]"#
+        );
+        assert_eq!(
+            error("{1|title}"),
+            r#"--format "{1|title}": unknown transform "title", use upper, lower, trim, substr, pad, lpad, or replace"#
+        );
+        assert_eq!(error("{1|pad}"), r#"--format "{1|pad}": pad requires :WIDTH[:FILL]"#);
+        assert_eq!(
+            error("{1|substr:x}"),
+            r#"--format "{1|substr:x}": substr "x": invalid digit found in string"#
+        );
+        assert_eq!(
+            error("{1|lpad:3:ab}"),
+            r#"--format "{1|lpad:3:ab}": lpad fill "ab" is not a single char"#
+        );
+        assert_eq!(
+            error("{1|upper:5}"),
+            r#"--format "{1|upper:5}": upper takes no arguments, got "5""#
+        );
+        assert_eq!(
+            error("{1|trim:}"),
+            r#"--format "{1|trim:}": trim takes no arguments, got """#
+        );
+        assert_eq!(
+            error("{1|substr:1:2:3}"),
+            r#"--format "{1|substr:1:2:3}": substr takes :START[:LEN], got "1:2:3""#
+        );
+        assert_eq!(
+            error("{1|lpad:3:0:x}"),
+            r#"--format "{1|lpad:3:0:x}": lpad takes :WIDTH[:FILL], got "3:0:x""#
+        );
+        assert_eq!(
+            error("{1|replace:/a/b/c}"),
+            r#"--format "{1|replace:/a/b/c}": replace takes :/RE/REPLACEMENT/, got "c}" after it"#
+        );
+        assert_eq!(
+            error("{1|replace:/a/}"),
+            r#"--format "{1|replace:/a/}": replace requires :/RE/REPLACEMENT/"#
+        );
+        assert!(
+            error("{1|replace:/(/x/}").starts_with(r#"--format "{1|replace:/(/x/}": replace "(": regex parse error"#)
+        );
+    }
+}
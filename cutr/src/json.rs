@@ -0,0 +1,182 @@
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+// --json reads a JSON object per line, the fields of a line are columns
+//
+// the columns are the top-level keys of the first object (the "file header"),
+// then the -f paths which are not a key, each column is a (name, JSON pointer)
+//
+// "-f user.id"         =>  dotted path, the pointer "/user/id"
+// "-f /events/0/ts"    =>  JSON pointer, array elements by index
+// "-f 2", "-f r/^ts/"  =>  indices and header regexes over the columns, as without --json
+#[derive(Debug)]
+pub struct Column {
+    pub name: String,
+    pointer: String,
+}
+
+// --missing policy for a selected column which is not in an object
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Missing {
+    Empty,
+    Null,
+    Skip,
+    Error,
+}
+
+// a dotted path or JSON pointer -f arg, r/REGEX/ and R/REGEX/ are patterns
+pub fn is_path(s: &str) -> bool {
+    let pattern = s.len() > 3 && (s.starts_with("r/") || s.starts_with("R/")) && s.ends_with('/');
+    !pattern && (s.starts_with('/') || (s.contains('.') && s.parse::<f64>().is_err()))
+}
+
+// a JSON pointer segment, '~' => "~0", '/' => "~1"
+fn segment(key: &str) -> String {
+    "/".to_string() + &key.replace('~', "~0").replace('/', "~1")
+}
+
+// the JSON pointer of a dotted path
+fn pointer(path: &str) -> String {
+    match path.starts_with('/') {
+        true => path.into(),
+        false => path.split('.').map(segment).collect(),
+    }
+}
+
+// the columns of the `header` object keys and the `paths` which are not keys
+pub fn columns(header: &Map<String, Value>, paths: &[String]) -> Vec<Column> {
+    let keys = header.keys().map(|key| Column {
+        name: key.clone(),
+        pointer: segment(key),
+    });
+    let paths = paths
+        .iter()
+        .filter(|path| !header.contains_key(*path))
+        .map(|path| Column {
+            name: path.clone(),
+            pointer: pointer(path),
+        });
+    keys.chain(paths).collect()
+}
+
+// the text of the `object` value of each column, strings are unquoted, other values are JSON
+// returns the fields and the value of each column, a missing field is empty or "null" text and None
+pub fn fields(object: &Value, columns: &[Column], missing: Missing) -> (Vec<Cow<'static, [u8]>>, Vec<Option<Value>>) {
+    columns
+        .iter()
+        .map(|column| match object.pointer(&column.pointer) {
+            Some(Value::String(s)) => (Cow::Owned(s.clone().into_bytes()), Some(Value::String(s.clone()))),
+            Some(value) => (Cow::Owned(value.to_string().into_bytes()), Some(value.clone())),
+            None => match missing {
+                Missing::Null => (Cow::Borrowed(&b"null"[..]), None),
+                _ => (Cow::Borrowed(&b""[..]), None),
+            },
+        })
+        .unzip()
+}
+
+// --missing skip and error for the selected `indices` of a line
+// returns whether the line is output, or the first missing column for error
+pub fn keep_line<'a>(
+    values: &[Option<Value>],
+    indices: &[usize],
+    columns: &'a [Column],
+    missing: Missing,
+) -> Result<bool, &'a Column> {
+    match indices.iter().find(|&&i| matches!(values.get(i), Some(None))) {
+        Some(&i) => match missing {
+            Missing::Skip => Ok(false),
+            Missing::Error => Err(&columns[i]),
+            _ => Ok(true),
+        },
+        None => Ok(true),
+    }
+}
+
+// the --output json value of a column, the original value, a missing one is null or ""
+pub fn output_value(value: Option<&Value>, missing: Missing) -> Value {
+    match (value, missing) {
+        (Some(value), _) => value.clone(),
+        (None, Missing::Null) => Value::Null,
+        (None, _) => Value::from(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn text(fields: &[Cow<[u8]>]) -> Vec<String> {
+        fields.iter().map(|f| String::from_utf8_lossy(f).into_owned()).collect()
+    }
+
+    #[test]
+    fn paths() {
+        for path in ["user.id", "/events/0/ts", "/", "a.b.c", "1.x"] {
+            assert!(is_path(path), "{path}");
+        }
+        for arg in ["user", "2", "1.5", "-1", "2-3", "r/^ts/", "R/a.b/", "r/\\.json$/"] {
+            assert!(!is_path(arg), "{arg}");
+        }
+    }
+
+    #[test]
+    fn pointers() {
+        assert_eq!(pointer("user.id"), "/user/id");
+        assert_eq!(pointer("events.0.ts"), "/events/0/ts");
+        assert_eq!(pointer("/a~1b/c"), "/a~1b/c");
+        assert_eq!(segment("a/b"), "/a~1b");
+        assert_eq!(segment("a~b"), "/a~0b");
+        assert_eq!(segment("~1/"), "/~01~1");
+
+        // escaped keys resolve the keys they were made from
+        let object = json!({"a/b": {"c~d": 1}, "~1": 2});
+        assert_eq!(object.pointer(&pointer("a/b.c~d")), Some(&json!(1)));
+        assert_eq!(object.pointer(&segment("~1")), Some(&json!(2)));
+    }
+
+    #[test]
+    fn header_columns() {
+        let header = json!({"id": 1, "user": {"name": "ann"}});
+        let paths = ["user.name".to_string(), "id".to_string(), "/tags/0".to_string()];
+        let columns = columns(header.as_object().unwrap(), &paths);
+        let names = columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        let pointers = columns.iter().map(|c| c.pointer.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["id", "user", "user.name", "/tags/0"]);
+        assert_eq!(pointers, ["/id", "/user", "/user/name", "/tags/0"]);
+    }
+
+    #[test]
+    fn missing_policies() {
+        let header = json!({"id": 1, "name": "ann", "tags": ["a"], "x": null});
+        let columns = columns(header.as_object().unwrap(), &["user.age".to_string()]);
+        let line = json!({"id": 2, "tags": ["b", "c"], "x": null});
+        let values = [Some(json!(2)), None, Some(json!(["b", "c"])), Some(json!(null)), None];
+
+        for missing in [Missing::Empty, Missing::Skip, Missing::Error] {
+            let (fields, found) = fields(&line, &columns, missing);
+            assert_eq!(text(&fields), ["2", "", r#"["b","c"]"#, "null", ""]);
+            assert_eq!(found, values);
+        }
+        let (fields, found) = fields(&line, &columns, Missing::Null);
+        assert_eq!(text(&fields), ["2", "null", r#"["b","c"]"#, "null", "null"]);
+        assert_eq!(found, values);
+
+        // the selected columns decide whether a line is kept
+        let keep = |indices: &[usize], missing| keep_line(&values, indices, &columns, missing).map_err(|c| &c.name);
+        for missing in [Missing::Empty, Missing::Null, Missing::Skip, Missing::Error] {
+            assert_eq!(keep(&[0, 2, 3], missing), Ok(true));
+        }
+        assert_eq!(keep(&[0, 4], Missing::Empty), Ok(true));
+        assert_eq!(keep(&[0, 4], Missing::Null), Ok(true));
+        assert_eq!(keep(&[0, 4], Missing::Skip), Ok(false));
+        assert_eq!(keep(&[4, 1], Missing::Error), Err(&"user.age".to_string()));
+
+        // --output json values are the original values, a missing one is null or ""
+        assert_eq!(output_value(values[2].as_ref(), Missing::Empty), json!(["b", "c"]));
+        assert_eq!(output_value(values[3].as_ref(), Missing::Empty), Value::Null);
+        assert_eq!(output_value(None, Missing::Null), Value::Null);
+        assert_eq!(output_value(None, Missing::Empty), json!(""));
+    }
+}
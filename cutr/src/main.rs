@@ -1,9 +1,12 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
 use regex::{Match, Regex};
-use std::fs::File;
-use std::io::{self, BufRead, Write};
-use tokenize::{error::TokenizeError, tokenizer_from_spec, TokenizationSpec, TokenizerType};
+use std::borrow::Cow;
+use std::io::{self, Write};
+use tokenize::{
+    error::TokenizeError, tokenizer::Tokenizer, tokenizer_from_spec, Encoding, SpecFormat, TokenizationSpec,
+    TokenizerType,
+};
 
 // clap arg parser
 mod argparse;
@@ -12,6 +15,23 @@ mod argparse;
 mod field_spec;
 use crate::field_spec::FieldSpec;
 
+// --format Template
+mod format;
+use crate::format::Template;
+
+// --where Predicate
+mod predicate;
+use crate::predicate::Predicate;
+
+// --output formats
+mod output;
+use crate::output::Output;
+use serde_json::Value;
+
+// --json input
+mod json;
+use crate::json::Missing;
+
 // ==============================================================
 // helper function to return the <usize> in a Regex captured match
 fn captured_index(cap: Match) -> Result<usize, Box<dyn std::error::Error>> {
@@ -20,110 +40,46 @@ fn captured_index(cap: Match) -> Result<usize, Box<dyn std::error::Error>> {
         .parse::<usize>()
         .with_context(|| format!("regex capture error? -f {cap:?}"))?)
 }
-// ==============================================================
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // behave like a typical unix utility
-    general::reset_sigpipe()?;
-    let mut stdout = io::stdout().lock();
-
-    // parse command line arguments
-    let args = argparse::get_args();
-
-    // extract state switches, all default to false
-    let (tab, trim, uniq, sorted, number, compliment, zero) = (
-        args.get_flag("tab"),        // -T
-        args.get_flag("trim"),       // -t
-        args.get_flag("uniq"),       // -u
-        args.get_flag("sorted"),     // -s
-        args.get_flag("number"),     // -n
-        args.get_flag("compliment"), // -c
-        args.get_flag("zero"),       // -z
-    );
-
-    // a capturing regex for [rR] expressions between slashes (/). e.g. -fr/foo/
-    //   Label        -f Arg                           Captured Text
-    //   -----------------------------------------------------------
-    //   {r_type}  |  -fr/Pattern/ or -fR/Pattern/  |  [rR]
-    //   {r_exp}   |  -fr/Pattern/ or -fR/Pattern/  |  "Pattern"
-    //let farg_slash_re = Regex::new(r"^(?P<r_type>[rR])/(?P<r_exp>.+)/$")?;
-    let farg_slash_re = Regex::new(
-        r"(?x)
-        ^ (?P<r_type>[rR])  # starts with [rR]
-        / (?P<r_exp>.+) /$  # ends with /pattern/",
-    )?;
 
-    // normalize isolated -f[rR] or sub-split on comma (,)
-    let mut fargs = vec![];
-    for fstr in args.get_many::<String>("fields").expect("required") {
-        match farg_slash_re.captures(fstr) {
-            Some(capture) => fargs.push(capture["r_type"].to_owned() + &capture["r_exp"]),
-            _ => fargs.extend(fstr.split(',').map(String::from).collect::<Vec<_>>()),
-        }
+// helper function to return the fields of a line, single bytes for -b
+fn split_line<'a>(
+    tokenizer: &'a Tokenizer,
+    line: &'a [u8],
+    encoding: Encoding,
+    bytes: bool,
+) -> Result<Vec<Cow<'a, [u8]>>, TokenizeError> {
+    match bytes {
+        true => Ok(line.chunks(1).map(Cow::Borrowed).collect()),
+        false => tokenizer.tokens_encoded(line, encoding),
     }
+}
 
-    // set `input_delim` to Option<String>
-    let input_delim = match args.get_one::<String>("input_delim") {
-        Some(delim) if delim == "\\t" => Some('\t'.to_string()),
-        Some(delim) => Some(delim).cloned(),
-        // check option -T
-        _ => match tab {
-            true => Some('\t'.to_string()),
-            false => None,
-        },
-    };
-
-    // set `output_delim` to String
-    //   handle special inputs representing TAB, NL
-    //   Use <str> as the output field separator.
-    //   Default is to use -d, or '\t'
-    let output_delim = match args.get_one::<String>("output_delim") {
-        Some(o) if o == "\\t" => "\t".to_string(),
-        Some(o) if o == "\\n" => "\n".to_string(),
-        Some(o) => o.to_string(),
-        // copy the input delimeter or set to a tab
-        None => match input_delim {
-            Some(ref d) => d.to_string(),
-            _ => '\t'.to_string(),
-        },
-    };
-
-    // build a TokenizerSpec from arg inputs
-    let mut tokenizer_spec = TokenizationSpec {
-        trimmed_tokens: trim,
-        ..Default::default()
-    };
-    if input_delim.is_some() {
-        tokenizer_spec.tokenizer_type = TokenizerType::SplitStr;
-        tokenizer_spec.tokenizer_init_param = input_delim;
+// helper function to check a --chars or --bytes list has only positions: N, N-M, N-, -N
+// (names and r/R patterns would resolve against the chars or bytes of line 1)
+fn check_positions(option: &str, list: &str) -> Result<()> {
+    let position_re = Regex::new(r"^(?:\d+(?:-\d*)?|-\d+)$")?;
+    match list.split(',').find(|p| !position_re.is_match(p)) {
+        Some(p) => Err(anyhow!("--{option} {p:?} is not a position, use N, N-M, N-, or -N")),
+        None => Ok(()),
     }
+}
 
-    // Build a tokenizer from a TokenizationSpec
-    let tokenizer =
-        tokenizer_from_spec(&tokenizer_spec).map_err(|e| TokenizeError::AcquireTokerError(e.to_string()))?;
-
-    // read input lines from a filename or stdin and collect into a Vec<String>
-    let lines = match args.get_one::<std::path::PathBuf>("FILE") {
-        Some(file) if file.as_os_str() != "-" => io::BufReader::new(
-            File::open(file).with_context(|| format!("could not open file `{:?}`", file.as_os_str()))?,
-        )
-        .lines()
-        .map(|line| line.expect("wtf"))
-        .collect::<Vec<_>>(),
-        _ => io::stdin()
-            .lock()
-            .lines()
-            .map(|line| line.expect("wtf"))
-            .collect::<Vec<_>>(),
-    };
-    let tokens_per_line = |n: usize| tokenizer.tokens(&lines[n]);
-
-    // set `file_header` to the field tokens of the first line or return
-    let file_header = match lines.is_empty() {
-        true => return Ok(()),
-        false => tokens_per_line(0),
-    };
+// the -f fields, --format template, and --where predicate resolved against a header
+struct Selection {
+    names: Vec<String>,
+    fields: Vec<FieldSpec>,
+    template: Option<Template>,
+    predicate: Option<Predicate>,
+}
 
+// helper function to resolve the `fargs`, --format, and --where against the header names
+fn selection(
+    fargs: &[String],
+    header_names: &[Cow<str>],
+    ignore_case: bool,
+    format: Option<&String>,
+    predicate: Option<&String>,
+) -> Result<Selection, Box<dyn std::error::Error>> {
     // convert `fargs` to a list of field classifications (enums)
     //   FieldSpec::Index
     //   FieldSpec::OpenRange
@@ -133,31 +89,41 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // a capturing regex
     //   Label             -f Arg         Captured Text
     //   ----------------------------------------------
-    //   {r_hdr}        |  -frPattern  |  "Pattern"
-    //   {r_data}       |  -fRPattern  |  "Pattern"
-    //   {start}-{end}  |  -f N-M      |  captures: "N" "M"
-    //   {start}-       |  -f N        |  captures: "N"
-    //   {last}         |  -f-N        |  captures: "N"
-    //let farg_re = Regex::new(r"^(:?r(?P<r_hdr>.+)|R(?P<r_data>.+)|(?P<start>\d+)-(?P<end>\d+)?|-(?P<last>\d+))$")?;
+    //   {r_hdr}        |  -fr/Pattern/ |  "Pattern"
+    //   {r_data}       |  -fR/Pattern/ |  "Pattern"
+    //   {start}-{end}  |  -f N-M       |  captures: "N" "M"
+    //   {start}-       |  -f N         |  captures: "N"
+    //   {last}         |  -f-N         |  captures: "N"
     let farg_re = Regex::new(
         r"(?x)
         ^(?:
-            r (?P<r_hdr>.+) |                 # header pattern
-            R (?P<r_data>.+) |                # data pattern
+            r/ (?P<r_hdr>.+) / |              # header pattern
+            R/ (?P<r_data>.+) / |             # data pattern
             (?P<start>\d+) - (?P<end>\d+)? |  # ranges N-M or N-
             -(?P<last>\d+)                    # last index -N
         )$",
     )?;
 
+    // header names take precedence over -fr/-fR patterns, numbers do not
+    // patterns are between slashes, any other name which is not in the header is an error (e.g. -f rate)
+    let numeric = |s: &str| s.chars().all(|c| c.is_ascii_digit() || c == '-');
+
     let mut field_enums = vec![];
     for s in fargs {
-        match farg_re.captures(&s) {
+        // -f name, -f name-name => FieldSpec::Name, FieldSpec::ClosedRange
+        if !numeric(s) {
+            if let Some(field) = FieldSpec::from_name(s, header_names, ignore_case) {
+                field_enums.push(field);
+                continue;
+            }
+        }
+        match farg_re.captures(s) {
             Some(capture) => {
                 // -f 'rPattern' => List of FieldSpec::Index
                 if let Some(regex) = capture.name("r_hdr") {
                     let re = Regex::new(regex.as_str())?;
                     field_enums.extend(
-                        file_header
+                        header_names
                             .iter()
                             .enumerate()
                             .filter(|(_, txt)| re.is_match(txt))
@@ -185,58 +151,469 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
             // -f N => FieldSpec::Index or parse() Err
-            None => field_enums.push(FieldSpec::Index(
-                s.parse::<usize>().with_context(|| format!("-f {s:?}"))?,
-            )),
+            None => field_enums.push(FieldSpec::Index(s.parse::<usize>().with_context(|| {
+                format!(
+                    "-f {s:?} is not an index, range, regex (r/REGEX/, R/REGEX/), or a header name in {header_names:?}"
+                )
+            })?)),
+        }
+    }
+
+    Ok(Selection {
+        names: header_names.iter().map(|name| name.to_string()).collect(),
+        fields: field_enums,
+        // --format replaces the joined field output
+        template: match format {
+            Some(template) => Some(Template::parse(template, header_names, ignore_case)?),
+            None => None,
+        },
+        // --where keeps lines by their field values, line 1 is tested too unless --header
+        predicate: match predicate {
+            Some(expr) => Some(Predicate::parse(expr, header_names, ignore_case)?),
+            None => None,
+        },
+    })
+}
+// ==============================================================
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // behave like a typical unix utility
+    general::reset_sigpipe()?;
+    let mut stdout = io::stdout().lock();
+
+    // parse command line arguments
+    let args = argparse::get_args();
+
+    // extract state switches, all default to false
+    let (tab, trim, uniq, sorted, number, compliment, zero, ignore_case, no_header) = (
+        args.get_flag("tab"),         // -T
+        args.get_flag("trim"),        // -t
+        args.get_flag("uniq"),        // -u
+        args.get_flag("sorted"),      // -s
+        args.get_flag("number"),      // -n
+        args.get_flag("compliment"),  // -c
+        args.get_flag("zero"),        // -z
+        args.get_flag("ignore_case"), // -i
+        args.get_flag("no_header"),   // --no-header
+    );
+
+    // a regex for [rR] expressions between slashes (/). e.g. -fr/foo/
+    // an isolated -f[rR]/Pattern/ is not split on comma (,)
+    let farg_slash_re = Regex::new(
+        r"(?x)
+        ^ [rR]      # starts with [rR]
+        / .+ /$     # ends with /pattern/",
+    )?;
+
+    // --chars and -b select with the -f positions over chars or bytes instead of fields
+    let bytes = args.contains_id("bytes");
+    for id in ["chars", "bytes"] {
+        for list in args.get_many::<String>(id).into_iter().flatten() {
+            check_positions(id, list)?;
         }
     }
+    let field_args = ["fields", "chars", "bytes"]
+        .into_iter()
+        .flat_map(|id| args.get_many::<String>(id).into_iter().flatten());
+
+    // keep isolated -f[rR]/Pattern/ or sub-split on comma (,)
+    let mut fargs = vec![];
+    for fstr in field_args {
+        match farg_slash_re.is_match(fstr) {
+            true => fargs.push(fstr.to_owned()),
+            false => fargs.extend(fstr.split(',').map(String::from).collect::<Vec<_>>()),
+        }
+    }
+
+    // set `input_delim` to Option<String>
+    let input_delim = match args.get_one::<String>("input_delim") {
+        Some(delim) if delim == "\\t" => Some('\t'.to_string()),
+        Some(delim) => Some(delim).cloned(),
+        // check option -T
+        _ => match tab {
+            true => Some('\t'.to_string()),
+            false => None,
+        },
+    };
+
+    // start from a TokenizationSpec file or the default spec
+    let mut tokenizer_spec = match args.get_one::<std::path::PathBuf>("spec") {
+        Some(path) => TokenizationSpec::from_path(path)
+            .with_context(|| format!("could not load spec `{:?}`", path.as_os_str()))?,
+        None => TokenizationSpec::default(),
+    };
+
+    // command line options override the spec
+    if trim {
+        tokenizer_spec.trimmed_tokens = true;
+    }
+    if args.get_flag("csv") {
+        tokenizer_spec.tokenizer_type = TokenizerType::Csv;
+        tokenizer_spec.tokenizer_init_param = input_delim.or(Some(",".into()));
+    } else if let Some(widths) = args.get_one::<String>("widths") {
+        tokenizer_spec.tokenizer_type = TokenizerType::FixedWidth;
+        tokenizer_spec.tokenizer_init_param = Some(widths.into());
+    } else if args.contains_id("chars") {
+        tokenizer_spec.tokenizer_type = TokenizerType::Grapheme;
+        tokenizer_spec.tokenizer_init_param = None;
+    } else if input_delim.is_some() {
+        tokenizer_spec.tokenizer_type = TokenizerType::SplitStr;
+        tokenizer_spec.tokenizer_init_param = input_delim;
+    }
+    if let Some(words) = args.get_one::<String>("stopwords") {
+        tokenizer_spec.stopwords = Some(words.parse()?);
+    }
+    if let Some(words) = args.get_one::<String>("keepwords") {
+        tokenizer_spec.keepwords = Some(words.parse()?);
+    }
+
+    // --output format of the selected fields
+    let output = match args.get_one::<String>("output").expect("default").as_ref() {
+        "csv" => Output::Csv,
+        "json" => Output::Json,
+        "ndjson" => Output::Ndjson,
+        _ => Output::Text,
+    };
+
+    // set `output_delim` to String
+    //   handle special inputs representing TAB, NL
+    //   Use <str> as the output field separator.
+    //   Default is to use -d, ',' for --output csv, nothing for --chars and -b, or '\t'
+    let output_delim = match args.get_one::<String>("output_delim") {
+        Some(o) if o == "\\t" => "\t".to_string(),
+        Some(o) if o == "\\n" => "\n".to_string(),
+        Some(o) => o.to_string(),
+        // copy the input delimeter or set to a tab
+        None => match (&tokenizer_spec.tokenizer_type, &tokenizer_spec.tokenizer_init_param) {
+            _ if output == Output::Csv => ','.to_string(),
+            _ if bytes => "".into(),
+            (TokenizerType::Grapheme, _) => "".into(),
+            (TokenizerType::SplitStr, Some(d)) => d.to_string(),
+            (TokenizerType::Csv, Some(d)) => d.to_string(),
+            (TokenizerType::Csv, None) => ','.to_string(),
+            _ => '\t'.to_string(),
+        },
+    };
+
+    // re-quote output fields of CSV input or --output csv
+    let csv = tokenizer_spec.tokenizer_type == TokenizerType::Csv || output == Output::Csv;
+
+    // Build a tokenizer from a TokenizationSpec
+    let tokenizer =
+        tokenizer_from_spec(&tokenizer_spec).map_err(|e| TokenizeError::AcquireTokerError(e.to_string()))?;
+
+    // --dump-spec, output the validated spec
+    if let Some(format) = args.get_one::<String>("dump_spec") {
+        let format = match format.as_ref() {
+            "toml" => SpecFormat::Toml,
+            _ => SpecFormat::Json,
+        };
+        write!(stdout, "{}", tokenizer_spec.to_format_string(format)?)?;
+        return Ok(());
+    }
+
+    // the input files, stdin when there are none
+    let files = match args.get_many::<std::path::PathBuf>("FILE") {
+        Some(files) => files.map(Some).collect::<Vec<_>>(),
+        None => vec![None],
+    };
+    let (with_filename, per_file_header) = (args.get_flag("with_filename"), args.get_flag("per_file_header"));
+    let (invert, header) = (args.get_flag("invert"), args.get_flag("header"));
+    let encoding = args
+        .get_one::<String>("encoding")
+        .expect("default")
+        .parse::<Encoding>()?;
+
+    // --null-data reads and writes NUL terminated records
+    let terminator = match args.get_flag("null_data") {
+        true => b'\0',
+        false => b'\n',
+    };
+
+    // --json reads an object per line, -f paths are columns after the keys of the file header
+    let json = args.get_flag("json");
+    let json_paths = fargs
+        .iter()
+        .filter(|s| json && json::is_path(s))
+        .cloned()
+        .collect::<Vec<_>>();
+    let missing = match args.get_one::<String>("missing").map(String::as_str) {
+        Some("null") => Missing::Null,
+        Some("skip") => Missing::Skip,
+        Some("error") => Missing::Error,
+        _ => Missing::Empty,
+    };
+    let mut columns = vec![];
+
+    // resolved on the first line, the "file header", of the input
+    // or of every file with --per-file-header
+    let mut resolved: Option<Selection> = None;
+
+    // --output json writes the line objects as one array
+    let mut records = 0;
+    if output == Output::Json {
+        stdout.write_all(b"[")?;
+    }
 
     // ==============================================================
     // process input lines, output joined fields
-    for i in 0..lines.len() {
-        let line_tokens = tokens_per_line(i);
-
-        // generate indices into `line_tokens` to extract
-        let indices = field_enums.iter().flat_map(|f| f.indices(&line_tokens));
+    for file in files {
+        // stream input lines from a filename or stdin
+        let name = || file.map_or("stdin".into(), |f| format!("{:?}", f.as_os_str()));
+        let read_error = || format!("could not read file `{}`", name());
+        let line_error = |n: usize| format!("file `{}` line {n}", name());
+        let lines = general::input_lines(file)
+            .with_context(|| format!("could not open file `{}`", name()))?
+            .terminator(terminator);
 
-        // collect the (unique)? (sorted)? set of indices
-        let indices = match uniq {
-            true => match sorted {
-                true => indices.unique().sorted().collect::<Vec<_>>(),
-                false => indices.unique().collect::<Vec<_>>(),
-            },
-            false => match sorted {
-                true => indices.sorted().collect::<Vec<_>>(),
-                false => indices.collect::<Vec<_>>(),
+        // -H filename
+        let filename = match with_filename {
+            true => match file {
+                Some(f) if f.as_os_str() != "-" => Some(f.to_string_lossy().into_owned()),
+                _ => Some("stdin".to_string()),
             },
+            false => None,
         };
 
-        // compliment the set if indices?
-        let indices = match compliment {
-            true => (0..line_tokens.len()).filter(|i| !indices.contains(i)).collect(),
-            false => indices,
-        };
+        // CSV records may span lines, `n` is the line number of the first line of a record
+        let mut first_line = true;
+        for record in tokenizer.records(lines) {
+            let (n, line) = record.with_context(read_error)?;
 
-        // current line number + output_delim
-        let line_number = match number {
-            true => (i + 1).to_string() + &output_delim,
-            false => "".into(),
-        };
+            // --json skips blank lines
+            if json && line.trim_ascii().is_empty() {
+                continue;
+            }
+
+            // the file header resolves -f names, -fr patterns, --format, and --where
+            let file_header = std::mem::take(&mut first_line) && (resolved.is_none() || per_file_header);
+
+            // the fields of the line, and the --json values of the fields, None when missing
+            let (line_tokens, values) = match json {
+                true => {
+                    let object = serde_json::from_slice::<Value>(&line).with_context(|| line_error(n))?;
+                    let keys = object
+                        .as_object()
+                        .with_context(|| format!("{}: not a JSON object", line_error(n)))?;
+                    if file_header {
+                        columns = json::columns(keys, &json_paths);
+                    }
+                    json::fields(&object, &columns, missing)
+                }
+                false => (
+                    split_line(&tokenizer, &line, encoding, bytes).with_context(|| line_error(n))?,
+                    vec![],
+                ),
+            };
+
+            // generate indices into `line_tokens` to extract, matching -fR against the (lossy) text
+            let line_text = line_tokens
+                .iter()
+                .map(|t| String::from_utf8_lossy(t))
+                .collect::<Vec<_>>();
+
+            if file_header {
+                let header_names = match json {
+                    true => columns.iter().map(|c| Cow::from(c.name.as_str())).collect(),
+                    false => line_text.clone(),
+                };
+                resolved = Some(selection(
+                    &fargs,
+                    &header_names,
+                    ignore_case,
+                    args.get_one::<String>("format"),
+                    args.get_one::<String>("where"),
+                )?);
+            }
+            let Selection {
+                names,
+                fields,
+                template,
+                predicate,
+            } = resolved.as_ref().expect("the file header resolves the selection");
+
+            // --no-header, the file header selects fields but is not output
+            if file_header && no_header {
+                continue;
+            }
+
+            // --where (--invert) filters lines before field extraction
+            if let Some(predicate) = predicate {
+                if !(file_header && header) && predicate.eval(&line_text) == invert {
+                    continue;
+                }
+            }
+
+            // filename + output_delim + current line number + output_delim
+            let line_number = match number {
+                true => Some(n.to_string()),
+                false => None,
+            };
+            let mut prefix = vec![];
+            for field in filename.iter().chain(line_number.iter()) {
+                match csv {
+                    true => prefix.extend_from_slice(&tokenize::csv::quote(field.as_bytes(), output_delim.as_bytes())),
+                    false => prefix.extend_from_slice(field.as_bytes()),
+                }
+                prefix.extend_from_slice(output_delim.as_bytes());
+            }
+
+            // output a line rendered from the --format template
+            if let Some(template) = template {
+                let rendered = template.render(&line_text);
+                if !rendered.is_empty() || !zero {
+                    stdout.write_all(&prefix)?;
+                    stdout.write_all(rendered.as_bytes())?;
+                    stdout.write_all(&[terminator])?;
+                }
+                continue;
+            }
+
+            let indices = fields.iter().flat_map(|f| f.indices(&line_text));
+
+            // collect the (unique)? (sorted)? set of indices
+            let indices = match uniq {
+                true => match sorted {
+                    true => indices.unique().sorted().collect::<Vec<_>>(),
+                    false => indices.unique().collect::<Vec<_>>(),
+                },
+                false => match sorted {
+                    true => indices.sorted().collect::<Vec<_>>(),
+                    false => indices.collect::<Vec<_>>(),
+                },
+            };
+
+            // compliment the set if indices?
+            let indices = match compliment {
+                true => (0..line_tokens.len()).filter(|i| !indices.contains(i)).collect(),
+                false => indices,
+            };
+
+            if indices.is_empty() && zero {
+                continue;
+            }
 
-        // output a line of joined fields
-        if !indices.is_empty() || !zero {
-            writeln!(
-                stdout,
-                "{}{}",
-                line_number,
-                indices
+            // --missing skip or error when a selected --json field is missing
+            match json::keep_line(&values, &indices, &columns, missing) {
+                Ok(true) => (),
+                Ok(false) => continue,
+                Err(column) => return Err(anyhow!("{}: missing {:?}", line_error(n), column.name).into()),
+            }
+
+            // output a JSON object keyed by the header names with --no-header or --json, otherwise by index
+            // --json values are output as they were read
+            if let Output::Json | Output::Ndjson = output {
+                let key = |i: usize| match (no_header || json, names.get(i)) {
+                    (true, Some(name)) => name.clone(),
+                    _ => (i + 1).to_string(),
+                };
+                let object = output::json_object(
+                    filename
+                        .iter()
+                        .map(|f| ("file".to_string(), Value::from(f.as_str())))
+                        .chain(number.then(|| ("line".to_string(), Value::from(n))))
+                        .chain(indices.iter().map(|&i| {
+                            let value = match json {
+                                true => json::output_value(values[i].as_ref(), missing),
+                                false => Value::from(line_text[i].as_ref()),
+                            };
+                            (key(i), value)
+                        })),
+                );
+                match output {
+                    Output::Json => {
+                        if records > 0 {
+                            stdout.write_all(b",")?;
+                        }
+                        stdout.write_all(b"\n")?;
+                        stdout.write_all(object.to_string().as_bytes())?;
+                    }
+                    _ => {
+                        stdout.write_all(object.to_string().as_bytes())?;
+                        stdout.write_all(&[terminator])?;
+                    }
+                }
+                records += 1;
+                continue;
+            }
+
+            // output a line of joined fields
+            // the fields are written as bytes, untouched in --encoding bytes mode
+            // CSV fields are re-quoted for the output delimiter
+            stdout.write_all(&prefix)?;
+            stdout.write_all(
+                &indices
                     .into_iter()
-                    .map(|i| line_tokens[i].to_owned())
+                    .map(|i| match csv {
+                        true => tokenize::csv::quote(&line_tokens[i], output_delim.as_bytes()),
+                        false => Cow::Borrowed(line_tokens[i].as_ref()),
+                    })
                     .collect::<Vec<_>>()
-                    .join(&output_delim)
+                    .join(output_delim.as_bytes()),
             )?;
+            stdout.write_all(&[terminator])?;
+        }
+    }
+    if output == Output::Json {
+        if records > 0 {
+            stdout.write_all(b"\n")?;
         }
+        stdout.write_all(b"]\n")?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the indices selected by `fargs` from a line of `header` fields
+    fn select(fargs: &[&str], header: &[&str]) -> Result<Vec<usize>, String> {
+        let fargs = fargs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let names = header.iter().map(|&s| Cow::from(s)).collect::<Vec<_>>();
+        match selection(&fargs, &names, false, None, None) {
+            Ok(selected) => Ok(selected.fields.iter().flat_map(|f| f.indices(header)).collect()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn names_and_patterns() {
+        let header = ["name", "region", "rate"];
+        assert_eq!(select(&["region", "1", "-1"], &header), Ok(vec![1, 0, 2]));
+        assert_eq!(select(&["name-region"], &header), Ok(vec![0, 1]));
+        assert_eq!(select(&["rate"], &header), Ok(vec![2]));
+        assert_eq!(select(&["r/^r/"], &header), Ok(vec![1, 2]));
+        assert_eq!(select(&["R/^na/"], &header), Ok(vec![0]));
+    }
+
+    #[test]
+    fn chars_positions() {
+        for list in ["1", "1-3", "3-1", "5-", "-1", "1-3,5-,-2"] {
+            assert!(check_positions("chars", list).is_ok(), "{list}");
+        }
+        for (list, p) in [
+            ("a", "a"),
+            ("rb", "rb"),
+            ("r/b/", "r/b/"),
+            ("1,,2", ""),
+            ("1-x", "1-x"),
+            ("--1", "--1"),
+        ] {
+            let err = check_positions("bytes", list).unwrap_err().to_string();
+            assert_eq!(err, format!("--bytes {p:?} is not a position, use N, N-M, N-, or -N"));
+        }
+    }
+
+    #[test]
+    fn unknown_names() {
+        // a name which is not in the header is not read as an r/R pattern
+        let header = ["name", "region"];
+        for name in ["Rate", "rate", "r^re", "x"] {
+            let err = select(&[name], &header).unwrap_err();
+            assert!(err.starts_with(&format!("-f {name:?} is not an index")), "{err}");
+            assert!(err.ends_with(r#"a header name in ["name", "region"]"#), "{err}");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde_json::{Map, Value};
+
+// --output format of the selected fields
+//    Text   -- fields joined by the output delimiter
+//    Csv    -- RFC 4180 quoted fields joined by ',' (or -o)
+//    Json   -- an array of objects, one per line
+//    Ndjson -- an object per line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    Text,
+    Csv,
+    Json,
+    Ndjson,
+}
+
+// a JSON object of (key, value) pairs in their order, a repeated key is given a suffix,
+// e.g. -f 1,1 is {"1", "1_2"} and a header named "line" with -n is {"line", "line_2"}
+pub fn json_object(pairs: impl IntoIterator<Item = (String, Value)>) -> Value {
+    let mut object = Map::new();
+    for (key, value) in pairs {
+        let mut unique = key.clone();
+        let mut n = 1;
+        while object.contains_key(&unique) {
+            n += 1;
+            unique = format!("{key}_{n}");
+        }
+        object.insert(unique, value);
+    }
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(keys: &[&str]) -> String {
+        json_object(keys.iter().enumerate().map(|(i, k)| (k.to_string(), Value::from(i)))).to_string()
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(object(&[]), "{}");
+        assert_eq!(object(&["b", "a", "c"]), r#"{"b":0,"a":1,"c":2}"#);
+        assert_eq!(object(&["1", "1", "1"]), r#"{"1":0,"1_2":1,"1_3":2}"#);
+        assert_eq!(object(&["line", "name", "line"]), r#"{"line":0,"name":1,"line_2":2}"#);
+        assert_eq!(object(&["a", "a_2", "a"]), r#"{"a":0,"a_2":1,"a_3":2}"#);
+    }
+
+    #[test]
+    fn values() {
+        let pairs = [
+            ("file".to_string(), json!("a \"b\".csv")),
+            ("x".to_string(), json!(null)),
+        ];
+        assert_eq!(json_object(pairs).to_string(), r#"{"file":"a \"b\".csv","x":null}"#);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::field_spec::FieldSpec;
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use std::cmp::Ordering;
+
+// A --where Predicate keeps or drops an input line by its field values
+//
+// "3 ~ /ERROR/"                   =>  field 3 matches the regex
+// "status == 200"                 =>  header field "status" equals 200
+// "size > 1e6 and user != root"   =>  numeric and string comparisons
+// "not (1 ~ /^#/ or 2 == \"\")"   =>  not, parentheses, quoted strings
+//
+// fields are N, -N, or header names, as in -f
+// operators: ~ !~ == != < <= > >=, combined with and (&&), or (||), not (!)
+// values compare as numbers when both sides parse as finite numbers, otherwise as strings
+// ("nan", "inf", and "infinity" are strings)
+#[derive(Debug)]
+pub enum Predicate {
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+    Match(FieldSpec, Regex),
+    Compare(FieldSpec, Comparison, String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String),
+    Str(String),
+    Re(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+}
+
+impl Predicate {
+    // parse `expr`, header names are resolved against `header`
+    pub fn parse<S: AsRef<str> + std::fmt::Debug>(expr: &str, header: &[S], ignore_case: bool) -> Result<Self> {
+        let toks = lex(expr).with_context(|| format!("--where {expr:?}"))?;
+        let mut parser = Parser {
+            toks: &toks,
+            pos: 0,
+            header,
+            ignore_case,
+        };
+        let predicate = parser.or().with_context(|| format!("--where {expr:?}"))?;
+        match parser.toks.get(parser.pos) {
+            None => Ok(predicate),
+            Some(tok) => bail!("--where {expr:?}: unexpected {tok:?}"),
+        }
+    }
+
+    pub fn eval<S: AsRef<str>>(&self, tokens: &[S]) -> bool {
+        match self {
+            Predicate::And(a, b) => a.eval(tokens) && b.eval(tokens),
+            Predicate::Or(a, b) => a.eval(tokens) || b.eval(tokens),
+            Predicate::Not(a) => !a.eval(tokens),
+            Predicate::Match(field, re) => re.is_match(field.text(tokens)),
+            Predicate::Compare(field, comparison, value) => {
+                let text = field.text(tokens);
+                let number = |s: &str| s.parse::<f64>().ok().filter(|n| n.is_finite());
+                let ordering = match (number(text.trim()), number(value)) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b),
+                    _ => Some(text.cmp(value.as_str())),
+                };
+                match comparison {
+                    Comparison::Eq => ordering == Some(Ordering::Equal),
+                    Comparison::Ne => ordering != Some(Ordering::Equal),
+                    Comparison::Lt => ordering == Some(Ordering::Less),
+                    Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Comparison::Gt => ordering == Some(Ordering::Greater),
+                    Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+        }
+    }
+}
+
+// split `expr` into Toks
+fn lex(expr: &str) -> Result<Vec<Tok>> {
+    let mut toks = vec![];
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        let tok = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Tok::LParen,
+            ')' => Tok::RParen,
+            '"' => Tok::Str(quoted(&mut chars, '"')?),
+            '/' => Tok::Re(quoted(&mut chars, '/')?),
+            '~' => Tok::Op("~"),
+            '&' if chars.next_if_eq(&'&').is_some() => Tok::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Tok::Or,
+            '!' if chars.next_if_eq(&'=').is_some() => Tok::Op("!="),
+            '!' if chars.next_if_eq(&'~').is_some() => Tok::Op("!~"),
+            '!' => Tok::Not,
+            '=' => {
+                chars.next_if_eq(&'=');
+                Tok::Op("==")
+            }
+            '<' if chars.next_if_eq(&'=').is_some() => Tok::Op("<="),
+            '<' => Tok::Op("<"),
+            '>' if chars.next_if_eq(&'=').is_some() => Tok::Op(">="),
+            '>' => Tok::Op(">"),
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()\"/~&|!=<>".contains(*c)) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "and" => Tok::And,
+                    "or" => Tok::Or,
+                    "not" => Tok::Not,
+                    _ => Tok::Word(word),
+                }
+            }
+        };
+        toks.push(tok);
+    }
+    Ok(toks)
+}
+
+// text up to an unescaped `end`, \<end> is the `end` char
+fn quoted(chars: &mut std::iter::Peekable<std::str::Chars>, end: char) -> Result<String> {
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.next_if_eq(&end).is_some() => text.push(end),
+            c if c == end => return Ok(text),
+            c => text.push(c),
+        }
+    }
+    bail!("missing closing {end}")
+}
+
+// recursive descent over the Toks
+//    or   := and (or and)*
+//    and  := term (and term)*
+//    term := not term | ( or ) | FIELD OP VALUE
+struct Parser<'a, S> {
+    toks: &'a [Tok],
+    pos: usize,
+    header: &'a [S],
+    ignore_case: bool,
+}
+
+impl<S: AsRef<str> + std::fmt::Debug> Parser<'_, S> {
+    fn next(&mut self) -> Option<&Tok> {
+        self.pos += 1;
+        self.toks.get(self.pos - 1)
+    }
+
+    fn accept(&mut self, tok: &Tok) -> bool {
+        match self.toks.get(self.pos) == Some(tok) {
+            true => {
+                self.pos += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<Predicate> {
+        let mut predicate = self.and()?;
+        while self.accept(&Tok::Or) {
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn and(&mut self) -> Result<Predicate> {
+        let mut predicate = self.term()?;
+        while self.accept(&Tok::And) {
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.term()?));
+        }
+        Ok(predicate)
+    }
+
+    fn term(&mut self) -> Result<Predicate> {
+        if self.accept(&Tok::Not) {
+            return Ok(Predicate::Not(Box::new(self.term()?)));
+        }
+        if self.accept(&Tok::LParen) {
+            let predicate = self.or()?;
+            return match self.accept(&Tok::RParen) {
+                true => Ok(predicate),
+                false => bail!("missing closing )"),
+            };
+        }
+
+        let (header, ignore_case) = (self.header, self.ignore_case);
+        let field = match self.next() {
+            Some(Tok::Word(name)) | Some(Tok::Str(name)) => FieldSpec::field(name, header, ignore_case)
+                .ok_or_else(|| anyhow!("{name:?} is not an index or a header name in {header:?}"))?,
+            tok => bail!("expected a field, found {tok:?}"),
+        };
+        let op = match self.next() {
+            Some(Tok::Op(op)) => *op,
+            tok => bail!("expected an operator, found {tok:?}"),
+        };
+        let value = match self.next() {
+            Some(Tok::Word(value)) | Some(Tok::Str(value)) | Some(Tok::Re(value)) => value.clone(),
+            tok => bail!("expected a value, found {tok:?}"),
+        };
+
+        let comparison = match op {
+            "~" | "!~" => {
+                let re = Regex::new(&value).with_context(|| format!("{op} {value:?}"))?;
+                let predicate = Predicate::Match(field, re);
+                return Ok(match op {
+                    "~" => predicate,
+                    _ => Predicate::Not(Box::new(predicate)),
+                });
+            }
+            "==" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            ">" => Comparison::Gt,
+            _ => Comparison::Ge,
+        };
+        Ok(Predicate::Compare(field, comparison, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: [&str; 3] = ["status", "size", "msg"];
+
+    // the lines of `lines` kept by `expr`, --invert keeps the others
+    fn filter<'a>(expr: &str, lines: &[[&'a str; 3]], invert: bool) -> Vec<&'a str> {
+        let predicate = Predicate::parse(expr, &HEADER, false).unwrap();
+        lines
+            .iter()
+            .filter(|line| predicate.eval(&line[..]) != invert)
+            .map(|line| line[2])
+            .collect()
+    }
+
+    // the error and its causes on one line
+    fn error(expr: &str) -> String {
+        format!("{:#}", Predicate::parse(expr, &HEADER, false).unwrap_err())
+    }
+
+    const LINES: [[&str; 3]; 5] = [
+        ["200", "512", "ok"],
+        ["404", "10", "ERROR not found"],
+        ["500", "2048", "ERROR server"],
+        ["200", "1e4", "big"],
+        ["-", "nan", "none"],
+    ];
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(
+            filter("3 ~ /ERROR/", &LINES, false),
+            ["ERROR not found", "ERROR server"]
+        );
+        assert_eq!(filter("msg !~ /^ERROR/", &LINES, false), ["ok", "big", "none"]);
+        assert_eq!(filter("status == 200", &LINES, false), ["ok", "big"]);
+        assert_eq!(
+            filter("status != 200", &LINES, false),
+            ["ERROR not found", "ERROR server", "none"]
+        );
+        assert_eq!(filter("msg == \"ERROR server\"", &LINES, false), ["ERROR server"]);
+
+        // numeric when both sides are numbers, 1e4 > 2048 > 512 > 10, "nan" is a string > "1000"
+        assert_eq!(filter("size > 1000", &LINES, false), ["ERROR server", "big", "none"]);
+        assert_eq!(filter("size <= 512", &LINES, false), ["ok", "ERROR not found"]);
+        assert_eq!(
+            filter("-2 >= 2048 and -2 != nan", &LINES, false),
+            ["ERROR server", "big"]
+        );
+        assert_eq!(filter("size < 100", &LINES, false), ["ERROR not found"]);
+
+        // non-finite values are strings
+        assert_eq!(filter("size == nan", &LINES, false), ["none"]);
+        assert_eq!(filter("size > inf", &LINES, false), ["none"]);
+    }
+
+    #[test]
+    fn precedence() {
+        // and binds tighter than or, not tighter than and
+        let expr = "status == 404 or status == 200 and size > 1000";
+        assert_eq!(filter(expr, &LINES, false), ["ERROR not found", "big"]);
+        let expr = "(status == 404 or status == 200) and size > 1000";
+        assert_eq!(filter(expr, &LINES, false), ["big"]);
+        let expr = "not status == 200 and not msg ~ /ERROR/";
+        assert_eq!(filter(expr, &LINES, false), ["none"]);
+        let expr = "!(status == 200 || msg ~ /ERROR/) && size == nan";
+        assert_eq!(filter(expr, &LINES, false), ["none"]);
+    }
+
+    #[test]
+    fn invert() {
+        assert_eq!(filter("3 ~ /ERROR/", &LINES, true), ["ok", "big", "none"]);
+        assert_eq!(
+            filter("status == 200 or size == nan", &LINES, true),
+            ["ERROR not found", "ERROR server"]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("status =="),
+            r#"--where "status ==": expected a value, found None"#
+        );
+        assert_eq!(
+            error("status 200"),
+            r#"--where "status 200": expected an operator, found Some(Word("200"))"#
+        );
+        assert_eq!(
+            error("== 200"),
+            r#"--where "== 200": expected a field, found Some(Op("=="))"#
+        );
+        assert_eq!(
+            error("(status == 200"),
+            r#"--where "(status == 200": missing closing )"#
+        );
+        assert_eq!(
+            error("status == 200)"),
+            r#"--where "status == 200)": unexpected RParen"#
+        );
+        assert_eq!(error("msg ~ /open"), r#"--where "msg ~ /open": missing closing /"#);
+        assert_eq!(
+            error("code == 1"),
+            r#"--where "code == 1": "code" is not an index or a header name in ["status", "size", "msg"]"#
+        );
+        assert!(error("msg ~ /(/").starts_with(r#"--where "msg ~ /(/": ~ "(": regex parse error"#));
+    }
+}
@@ -16,6 +16,65 @@ where
     Ok(io::BufReader::new(file).lines())
 }
 
+// ==============================================================
+// InputLines streams the raw lines of a file or STDIN one at a time
+//
+// line endings ("\n" or "\r\n") are removed like BufRead::lines(),
+// the bytes are not decoded (see tokenize::Encoding),
+// a read error is returned with its line number
+//
+// terminator(b'\0') reads NUL terminated records instead of lines
+pub struct InputLines<R> {
+    reader: R,
+    line_number: usize,
+    terminator: u8,
+}
+impl<R: BufRead> InputLines<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_number: 0,
+            terminator: b'\n',
+        }
+    }
+
+    pub fn terminator(mut self, terminator: u8) -> Self {
+        self.terminator = terminator;
+        self
+    }
+}
+impl<R: BufRead> Iterator for InputLines<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = vec![];
+        self.line_number += 1;
+        match self.reader.read_until(self.terminator, &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.last() == Some(&self.terminator) {
+                    line.pop();
+                    if self.terminator == b'\n' && line.ends_with(b"\r") {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(io::Error::new(e.kind(), format!("line {}: {e}", self.line_number)))),
+        }
+    }
+}
+
+// Opens the lines of a file, or STDIN when `filename` is None or "-"
+pub fn input_lines(filename: Option<&PathBuf>) -> io::Result<InputLines<Box<dyn BufRead>>> {
+    let reader: Box<dyn BufRead> = match filename {
+        Some(file) if file.as_os_str() != "-" => Box::new(io::BufReader::new(File::open(file)?)),
+        _ => Box::new(io::stdin().lock()),
+    };
+    Ok(InputLines::new(reader))
+}
+// ==============================================================
+
 // Reads the lines of a file, trims and returns them as a Vec of the supplied type
 pub fn read_trimmed_data_lines<T>(
     filename: Option<&PathBuf>,
@@ -89,3 +148,22 @@ pub fn reset_sigpipe() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_lines() {
+        let lines = InputLines::new(&b"a\r\nb\n\nc \xff"[..])
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lines, vec![&b"a"[..], b"b", b"", b"c \xff"]);
+
+        let records = InputLines::new(&b"a\r\nb\0\0c"[..])
+            .terminator(b'\0')
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records, vec![&b"a\r\nb"[..], b"", b"c"]);
+    }
+}
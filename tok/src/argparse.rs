@@ -42,7 +42,7 @@ pub fn get_args() -> ArgMatches {
                 .long("tokenizer")
                 .value_name("str")
                 .default_value("whitespace")
-                .help("Use <str> as the tokenizer (ss, us, uw, ws, rb)"),
+                .help("Use <str> as the tokenizer (ss, us, uw, ws, rb, rm, rs, csv, gr, fw)"),
         )
         .arg(
             Arg::new("tokenizer_param")
@@ -50,6 +50,76 @@ pub fn get_args() -> ArgMatches {
                 .long("param")
                 .value_name("str")
                 .help("Use <str> to initialize the tokenizer"),
+        )
+        .arg(
+            Arg::new("stopwords")
+                .long("stopwords")
+                .value_name("list")
+                .help("Discard tokens in <list>: a builtin (english, lucene), @file, or comma separated words"),
+        )
+        .arg(
+            Arg::new("keepwords")
+                .long("keepwords")
+                .value_name("list")
+                .help("Keep only tokens in <list>: a builtin (english, lucene), @file, or comma separated words"),
+        )
+        .arg(
+            Arg::new("ngrams")
+                .long("ngrams")
+                .value_name("range")
+                .help("Output n-grams of the tokens for every n in <range> (N or N-M)"),
+        )
+        .arg(
+            Arg::new("joiner")
+                .long("joiner")
+                .value_name("str")
+                .help("Join the tokens of a word n-gram with <str> (default ' ')"),
+        )
+        .arg(
+            Arg::new("char_ngrams")
+                .long("char-ngrams")
+                .action(clap::ArgAction::SetTrue)
+                .help("Output char n-grams (shingles) of each token instead of word n-grams"),
+        )
+        .arg(
+            Arg::new("pad")
+                .long("pad")
+                .value_name("char")
+                .value_parser(value_parser!(char))
+                .help("Pad each token with <char> before taking char n-grams"),
+        )
+        .arg(
+            Arg::new("encoding")
+                .long("encoding")
+                .value_name("mode")
+                .default_value("strict")
+                .value_parser(["strict", "lossy", "bytes"])
+                .help("Invalid UTF-8 input: error (strict), replace with U+FFFD (lossy), or output the original bytes (bytes)"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("format")
+                .default_value("debug")
+                .value_parser(["debug", "csv", "json", "ndjson"])
+                .help("Output each line as a debug list, a csv row, or {line, tokens, spans} json array items or ndjson"),
+        )
+        .arg(
+            Arg::new("spec")
+                .long("spec")
+                .value_name("file")
+                .value_parser(value_parser!(PathBuf))
+                .help("Load a TokenizationSpec (JSON or TOML), command line options override its values"),
+        )
+        .arg(
+            Arg::new("dump_spec")
+                .long("dump-spec")
+                .value_name("format")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("json")
+                .value_parser(["json", "toml"])
+                .help("Print the TokenizationSpec in <format> (default json) and exit"),
         );
     app.get_matches_from(env::args().collect::<Vec<String>>())
 }
@@ -1,8 +1,12 @@
 use anyhow::{Context, Result};
+use clap::parser::ValueSource;
 use general::reset_sigpipe;
-use std::fs::File;
-use std::io::{self, BufRead, Error, ErrorKind, Write};
-use tokenize::{error::TokenizeError, tokenizer_from_spec, TokenizationSpec, TokenizerType};
+use serde_json::{json, Value};
+use std::io::{self, Error, ErrorKind, Write};
+use tokenize::{
+    error::TokenizeError, tokenizer::Tokenizer, tokenizer_from_spec, Encoding, SpecFormat, TokenizationSpec,
+    TokenizerType,
+};
 
 // clap arg parser
 mod argparse;
@@ -15,19 +19,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // parse command line arguments
     let args = argparse::get_args();
 
-    // build a TokenizationSpec from arg inputs
-    let tokenizer_spec = TokenizationSpec {
-        downcase_text: args.get_flag("downcase"),
-        trimmed_tokens: args.get_flag("trimmed"),
-        tokenizer_init_param: args.get_one::<String>("tokenizer_param").cloned(),
-        filter_tokens_re: args.get_one::<String>("regex").cloned(),
-        tokenizer_type: match args.get_one::<String>("tokenizer") {
+    // start from a TokenizationSpec file or the default spec
+    let mut tokenizer_spec = match args.get_one::<std::path::PathBuf>("spec") {
+        Some(path) => TokenizationSpec::from_path(path)
+            .with_context(|| format!("could not load spec `{:?}`", path.as_os_str()))?,
+        None => TokenizationSpec::default(),
+    };
+
+    // command line options override the spec
+    if args.get_flag("downcase") {
+        tokenizer_spec.downcase_text = true;
+    }
+    if args.get_flag("trimmed") {
+        tokenizer_spec.trimmed_tokens = true;
+    }
+    if let Some(param) = args.get_one::<String>("tokenizer_param") {
+        tokenizer_spec.tokenizer_init_param = Some(param.into());
+    }
+    if let Some(re) = args.get_one::<String>("regex") {
+        tokenizer_spec.filter_tokens_re = Some(re.into());
+    }
+    if let Some(words) = args.get_one::<String>("stopwords") {
+        tokenizer_spec.stopwords = Some(words.parse()?);
+    }
+    if let Some(words) = args.get_one::<String>("keepwords") {
+        tokenizer_spec.keepwords = Some(words.parse()?);
+    }
+    if let Some(range) = args.get_one::<String>("ngrams") {
+        tokenizer_spec.ngrams = Some(range.parse()?);
+    }
+    if args.contains_id("joiner") || args.get_flag("char_ngrams") || args.contains_id("pad") {
+        let ngrams = tokenizer_spec.ngrams.get_or_insert_with(Default::default);
+        if let Some(joiner) = args.get_one::<String>("joiner") {
+            ngrams.joiner = joiner.into();
+        }
+        if args.get_flag("char_ngrams") {
+            ngrams.chars = true;
+        }
+        if let Some(pad) = args.get_one::<char>("pad") {
+            ngrams.pad = Some(*pad);
+        }
+    }
+    if args.value_source("tokenizer") == Some(ValueSource::CommandLine) {
+        tokenizer_spec.tokenizer_type = match args.get_one::<String>("tokenizer") {
             Some(name) => match name.as_ref() {
                 "ss" | "splitstr" => TokenizerType::SplitStr,
                 "us" | "unicode_segment" => TokenizerType::UnicodeSegment,
                 "uw" | "unicode_word" => TokenizerType::UnicodeWord,
                 "ws" | "whitespace" => TokenizerType::Whitespace,
                 "rb" | "regexboundary" => TokenizerType::RegexBoundary,
+                "rm" | "regexmatch" => TokenizerType::RegexMatch,
+                "rs" | "regexsplit" => TokenizerType::RegexSplit,
+                "csv" => TokenizerType::Csv,
+                "gr" | "grapheme" => TokenizerType::Grapheme,
+                "fw" | "fixedwidth" => TokenizerType::FixedWidth,
                 _ => {
                     return Err(Box::new(Error::new(
                         ErrorKind::InvalidInput,
@@ -36,32 +81,154 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             },
             None => return Err(Box::new(Error::new(ErrorKind::InvalidInput, "No tokenizer"))),
-        },
-    };
+        };
+    }
 
     // Build a tokenizer from a TokenizationSpec
     let tokenizer =
         tokenizer_from_spec(&tokenizer_spec).map_err(|e| TokenizeError::AcquireTokerError(e.to_string()))?;
 
-    // read input lines from a filename or stdin and collect into a Vec<String>
-    let lines = match args.get_one::<std::path::PathBuf>("FILE") {
-        Some(file) if file.as_os_str() != "-" => io::BufReader::new(
-            File::open(file).with_context(|| format!("could not open file `{:?}`", file.as_os_str()))?,
-        )
-        .lines()
-        .map(|line| line.expect("wtf"))
-        .collect::<Vec<_>>(),
-        _ => io::stdin()
-            .lock()
-            .lines()
-            .map(|line| line.expect("wtf"))
-            .collect::<Vec<_>>(),
-    };
+    // --dump-spec, output the validated spec
+    if let Some(format) = args.get_one::<String>("dump_spec") {
+        let format = match format.as_ref() {
+            "toml" => SpecFormat::Toml,
+            _ => SpecFormat::Json,
+        };
+        write!(stdout, "{}", tokenizer_spec.to_format_string(format)?)?;
+        return Ok(());
+    }
+
+    // stream input lines from a filename or stdin
+    let file = args.get_one::<std::path::PathBuf>("FILE");
+    let name = || file.map_or("stdin".into(), |f| format!("{:?}", f.as_os_str()));
+    let lines = general::input_lines(file).with_context(|| format!("could not open file `{}`", name()))?;
 
-    for line in lines {
-        let tokens = tokenizer.tokens(&line);
-        //writeln!(stdout, "{}", tokens.join(""))?;
-        writeln!(stdout, "{tokens:?}")?;
+    let encoding = args
+        .get_one::<String>("encoding")
+        .expect("default")
+        .parse::<Encoding>()?;
+    let output = args.get_one::<String>("output").expect("default");
+
+    // --output json writes the line records as one array
+    let mut records = 0;
+    if output == "json" {
+        write!(stdout, "[")?;
+    }
+    // CSV records may span lines, `n` is the line number of the first line of a record
+    for record in tokenizer.records(lines) {
+        let (n, line) = record.with_context(|| format!("could not read file `{}`", name()))?;
+        let context = || format!("file `{}` line {n}", name());
+        if output == "json" || output == "ndjson" {
+            let record = json_record(&tokenizer, n, &line, encoding).with_context(context)?;
+            match output == "json" {
+                true => write!(stdout, "{}\n{record}", if records > 0 { "," } else { "" })?,
+                false => writeln!(stdout, "{record}")?,
+            }
+            records += 1;
+            continue;
+        }
+        let tokens = tokenizer.tokens_encoded(&line, encoding).with_context(context)?;
+        match output.as_ref() {
+            // the tokens as one RFC 4180 row
+            "csv" => {
+                stdout.write_all(
+                    &tokens
+                        .iter()
+                        .map(|t| tokenize::csv::quote(t, b","))
+                        .collect::<Vec<_>>()
+                        .join(&b","[..]),
+                )?;
+                writeln!(stdout)?;
+            }
+            //writeln!(stdout, "{}", tokens.join(""))?;
+            _ => writeln!(
+                stdout,
+                "[{}]",
+                tokens.iter().map(|t| debug_token(t)).collect::<Vec<_>>().join(", ")
+            )?,
+        }
+    }
+    if output == "json" {
+        if records > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "]")?;
     }
     Ok(())
 }
+
+// A --output json record of the line number, the tokens, and the spans of UTF-8 lines,
+// the tokens of a UTF-8 line are the texts of its token spans
+fn json_record(tokenizer: &Tokenizer, n: usize, line: &[u8], encoding: Encoding) -> Result<Value, TokenizeError> {
+    Ok(match std::str::from_utf8(line) {
+        Ok(text) => {
+            let spans = tokenizer.spans(text);
+            let tokens = spans.iter().filter(|s| tokenizer.is_token(s)).map(|s| &s.text);
+            json!({"line": n, "tokens": tokens.collect::<Vec<_>>(), "spans": spans})
+        }
+        Err(_) => {
+            let tokens = tokenizer.tokens_encoded(line, encoding)?;
+            json!({"line": n, "tokens": tokens.iter().map(|t| String::from_utf8_lossy(t)).collect::<Vec<_>>()})
+        }
+    })
+}
+
+// Debug format of a token, bytes which are not UTF-8 are written as \xNN
+fn debug_token(token: &[u8]) -> String {
+    let mut text = String::from('"');
+    for chunk in token.utf8_chunks() {
+        let valid = format!("{:?}", chunk.valid());
+        text += &valid[1..valid.len() - 1];
+        for b in chunk.invalid() {
+            text += &format!("\\x{b:02x}");
+        }
+    }
+    text + "\""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenizer(downcase: bool) -> Tokenizer {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::Whitespace,
+            downcase_text: downcase,
+            ..Default::default()
+        };
+        tokenizer_from_spec(&spec).unwrap()
+    }
+
+    #[test]
+    fn utf8_records() {
+        let record = json_record(&tokenizer(true), 3, "Ab  ÇD".as_bytes(), Encoding::Strict).unwrap();
+        assert_eq!(record["line"], 3);
+        assert_eq!(record["tokens"], json!(["ab", "çd"]));
+        let spans = record["spans"].as_array().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(
+            (&spans[1]["text"], &spans[1]["byte_start"], &spans[1]["byte_end"]),
+            (&json!("çd"), &json!(4), &json!(7))
+        );
+        assert_eq!((&spans[1]["char_start"], &spans[1]["char_end"]), (&json!(4), &json!(6)));
+
+        let record = json_record(&tokenizer(false), 1, b"", Encoding::Strict).unwrap();
+        assert_eq!(record, json!({"line": 1, "tokens": [], "spans": []}));
+    }
+
+    #[test]
+    fn invalid_utf8_records() {
+        let line = b"a b\xff";
+        assert!(json_record(&tokenizer(false), 1, line, Encoding::Strict).is_err());
+        for encoding in [Encoding::Lossy, Encoding::Bytes] {
+            let record = json_record(&tokenizer(false), 2, line, encoding).unwrap();
+            assert_eq!(record, json!({"line": 2, "tokens": ["a", "b\u{fffd}"]}));
+        }
+    }
+
+    #[test]
+    fn debug_tokens() {
+        assert_eq!(debug_token(b"a\"b"), r#""a\"b""#);
+        assert_eq!(debug_token(b"caf\xe9\n"), r#""caf\xe9\n""#);
+    }
+}
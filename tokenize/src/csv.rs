@@ -0,0 +1,254 @@
+use std::borrow::Cow;
+
+//***********************************************
+//               Csv Tokenizer
+//   RFC 4180 fields separated by a delimiter
+//
+//   "Smith, John"  -- quoted field, embedded delimiter
+//   "say ""hi"""   -- escaped quotes
+//   5"" screen     -- an unquoted field, its quotes are literal
+//   "x"y           -- text after the closing quote is kept, "xy"
+//   "two
+//   lines"         -- embedded newline (see ends_quoted)
+//***********************************************
+
+// the longest record of a quoted field continued on following lines, in bytes
+pub const MAX_RECORD: usize = 1 << 24;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvTokenizer {
+    delimiter: String,
+}
+
+impl Default for CsvTokenizer {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl CsvTokenizer {
+    // the delimiter defaults to ","
+    pub fn new(delimiter: Option<String>) -> Self {
+        Self {
+            delimiter: delimiter.unwrap_or_else(|| ",".into()),
+        }
+    }
+
+    // split the first field from `text`, returns:
+    //    the field as written, its quotes included
+    //    whether the field is quoted
+    //    the text after the delimiter, None at the end of the record
+    //    false when the text ends inside the quoted field
+    //
+    // text between the closing quote and the delimiter is part of the field (see unquote)
+    fn field<'a>(&self, text: &'a str) -> (&'a str, bool, Option<&'a str>, bool) {
+        let split = |end: usize| match text[end..].find(&self.delimiter) {
+            Some(d) => (&text[..end + d], Some(&text[end + d + self.delimiter.len()..])),
+            None => (text, None),
+        };
+        match text.starts_with('"') {
+            true => match closing_quote(text) {
+                Some(q) => {
+                    let (field, next) = split(q + 1);
+                    (field, true, next, true)
+                }
+                None => (text, true, None, false),
+            },
+            false => {
+                let (field, next) = split(0);
+                (field, false, next, true)
+            }
+        }
+    }
+
+    // references into `text` of the fields as written, quotes included (see unquote)
+    //
+    // an empty record has no fields
+    pub fn ref_words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let mut rest = match text.is_empty() {
+            true => None,
+            false => Some(text),
+        };
+        std::iter::from_fn(move || {
+            let (field, _, next, _) = self.field(rest?);
+            rest = next;
+            Some(field)
+        })
+    }
+
+    pub fn words(&self, text: &str) -> Vec<String> {
+        self.ref_words(text).map(|w| unquote(w).into_owned()).collect()
+    }
+
+    // false when `text` ends inside a quoted field, the record continues on the next line
+    pub fn is_complete(&self, text: &str) -> bool {
+        !self.ends_quoted(text.as_bytes(), false)
+    }
+
+    // true when a record is inside a quoted field at the end of `line`, `quoted` is the
+    // state at the end of the previous line of the record
+    //
+    // the lines of a record are scanned once each, as they are read
+    pub fn ends_quoted(&self, line: &[u8], quoted: bool) -> bool {
+        enum State {
+            FieldStart,
+            Unquoted,
+            Quoted,
+            Closed,
+        }
+        let delimiter = self.delimiter.as_bytes();
+        let mut state = match quoted {
+            true => State::Quoted,
+            false => State::FieldStart,
+        };
+        let mut i = 0;
+        while i < line.len() {
+            match state {
+                State::Quoted => match (line[i], line.get(i + 1)) {
+                    // escaped quote
+                    (b'"', Some(b'"')) => i += 1,
+                    // closing quote, text up to the delimiter is literal field content
+                    (b'"', _) => state = State::Closed,
+                    _ => (),
+                },
+                _ if line[i..].starts_with(delimiter) => {
+                    state = State::FieldStart;
+                    i += delimiter.len();
+                    continue;
+                }
+                State::FieldStart if line[i] == b'"' => state = State::Quoted,
+                State::FieldStart => state = State::Unquoted,
+                State::Unquoted | State::Closed => (),
+            }
+            i += 1;
+        }
+        matches!(state, State::Quoted)
+    }
+}
+
+// the byte offset of the closing quote of the quoted field beginning `field`
+fn closing_quote(field: &str) -> Option<usize> {
+    let bytes = field.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            // escaped quote
+            (b'"', Some(b'"')) => i += 2,
+            (b'"', _) => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+// the text of a field returned by ref_words()
+//
+// an unquoted field is its text, quotes included, a quoted field is the text between
+// its quotes with "" => ", followed by any text after the closing quote ("x"y => xy)
+pub fn unquote(field: &str) -> Cow<'_, str> {
+    if !field.starts_with('"') {
+        return Cow::Borrowed(field);
+    }
+    let (quoted, after) = match closing_quote(field) {
+        Some(q) => (&field[1..q], &field[q + 1..]),
+        // a record which ends inside the quoted field
+        None => (&field[1..], ""),
+    };
+    match quoted.contains("\"\"") || !after.is_empty() {
+        true => Cow::Owned(quoted.replace("\"\"", "\"") + after),
+        false => Cow::Borrowed(quoted),
+    }
+}
+
+// quote a field for output when it contains the delimiter, a quote, or a line break
+pub fn quote<'a>(field: &'a [u8], delimiter: &[u8]) -> Cow<'a, [u8]> {
+    let embedded = !delimiter.is_empty() && field.windows(delimiter.len()).any(|w| w == delimiter);
+    match embedded || field.iter().any(|b| matches!(b, b'"' | b'\n' | b'\r')) {
+        true => {
+            let mut quoted = Vec::with_capacity(field.len() + 2);
+            quoted.push(b'"');
+            for &b in field {
+                if b == b'"' {
+                    quoted.push(b'"');
+                }
+                quoted.push(b);
+            }
+            quoted.push(b'"');
+            Cow::Owned(quoted)
+        }
+        false => Cow::Borrowed(field),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields() {
+        let toker = CsvTokenizer::default();
+        assert_eq!(
+            toker.words(r#"1,"Smith, John","say ""hi""",,x"#),
+            vec!["1", "Smith, John", r#"say "hi""#, "", "x"]
+        );
+        assert_eq!(toker.words("a,"), vec!["a", ""]);
+        assert_eq!(toker.words(""), Vec::<String>::new());
+        assert_eq!(toker.words(r#""""#), vec![""]);
+        assert_eq!(toker.words("\"two\nlines\",b"), vec!["two\nlines", "b"]);
+
+        let toker = CsvTokenizer::new(Some("\t".into()));
+        assert_eq!(toker.words("a\t\"b\tc\"\td"), vec!["a", "b\tc", "d"]);
+    }
+
+    #[test]
+    fn literal_quotes() {
+        let toker = CsvTokenizer::default();
+        // quotes in an unquoted field are its text
+        assert_eq!(toker.words(r#"5"" screen,c"#), vec![r#"5"" screen"#, "c"]);
+        assert_eq!(toker.words(r#"a"b,"c""d""#), vec![r#"a"b"#, r#"c"d"#]);
+        assert!(matches!(unquote(r#"5"" screen"#), Cow::Borrowed(_)));
+
+        // text after the closing quote is part of the field
+        assert_eq!(toker.words(r#""x"junk,y"#), vec!["xjunk", "y"]);
+        assert_eq!(toker.words(r#""x""y"z"w",v"#), vec![r#"x"yz"w""#, "v"]);
+        assert_eq!(
+            toker.ref_words(r#""x"junk,y"#).collect::<Vec<_>>(),
+            vec![r#""x"junk"#, "y"]
+        );
+        assert!(toker.is_complete(r#""x"ju"nk,y"#));
+        assert!(matches!(unquote(r#""plain""#), Cow::Borrowed("plain")));
+
+        // a record which ends inside a quoted field
+        assert_eq!(toker.words(r#"a,"b""c"#), vec!["a", r#"b"c"#]);
+    }
+
+    #[test]
+    fn is_complete() {
+        let toker = CsvTokenizer::default();
+        assert!(toker.is_complete(r#"a,"b,c",d"#));
+        assert!(toker.is_complete(r#"a,"b ""quoted"" c""#));
+        assert!(!toker.is_complete(r#"a,"b"#));
+        assert!(!toker.is_complete(r#"a,"b"","#));
+        assert!(toker.is_complete("a,\"b\nc\""));
+
+        // a record continued on the next line
+        assert!(toker.ends_quoted(b"a,\"b", false));
+        assert!(toker.ends_quoted(b"still \"\" open", true));
+        assert!(!toker.ends_quoted(b"closed\",c", true));
+        assert!(!toker.ends_quoted(b"\"", true));
+        assert!(!toker.ends_quoted(b"a\"b,c", false));
+
+        let toker = CsvTokenizer::new(Some("::".into()));
+        assert!(toker.ends_quoted(b"a::\"b::c", false));
+        assert!(!toker.ends_quoted(b"a:\"b", false));
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(quote(b"plain", b","), &b"plain"[..]);
+        assert_eq!(quote(b"Smith, John", b","), &b"\"Smith, John\""[..]);
+        assert_eq!(quote(b"say \"hi\"", b"\t"), &b"\"say \"\"hi\"\"\""[..]);
+        assert_eq!(quote(b"a\nb", b","), &b"\"a\nb\""[..]);
+        assert_eq!(quote(b"a,b", b"\t"), &b"a,b"[..]);
+    }
+}
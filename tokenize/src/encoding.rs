@@ -0,0 +1,54 @@
+use crate::error::TokenizeError;
+use std::str::FromStr;
+
+//================================================
+// Encoding is the policy for input which is not
+// valid UTF-8
+//    Strict -- invalid UTF-8 is a TokenizeError
+//    Lossy  -- invalid sequences become U+FFFD
+//    Bytes  -- tokenize lossily, tokens keep the
+//              original bytes of the input
+//================================================
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Strict,
+    Lossy,
+    Bytes,
+}
+
+pub const ENCODING_NAMES: [&str; 3] = ["strict", "lossy", "bytes"];
+
+impl FromStr for Encoding {
+    type Err = TokenizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(Self::Strict),
+            "lossy" => Ok(Self::Lossy),
+            "bytes" => Ok(Self::Bytes),
+            _ => Err(TokenizeError::InvalidEncodingError(format!(
+                "{s:?}, use one of {ENCODING_NAMES:?}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        for (name, encoding) in ENCODING_NAMES
+            .iter()
+            .zip([Encoding::Strict, Encoding::Lossy, Encoding::Bytes])
+        {
+            assert_eq!(name.parse::<Encoding>().unwrap(), encoding);
+        }
+        assert!(matches!(
+            "latin1".parse::<Encoding>(),
+            Err(TokenizeError::InvalidEncodingError(_))
+        ));
+    }
+}
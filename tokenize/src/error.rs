@@ -9,6 +9,24 @@ pub enum TokenizeError {
     #[error("InvalidTokenizer: {0}")]
     InvalidTokenizerError(String),
 
+    // `position` is the byte offset of the error in `pattern` when the regex parser reports one
+    #[error(
+        "InvalidRegex: {field} {pattern:?}{}: {reason}",
+        .position.map(|p| format!(" at position {p}")).unwrap_or_default()
+    )]
+    InvalidRegexError {
+        field: String,
+        pattern: String,
+        position: Option<usize>,
+        reason: String,
+    },
+
+    #[error("InvalidWordList: {field} {reason}")]
+    InvalidWordListError { field: String, reason: String },
+
+    #[error("InvalidEncoding: {0}")]
+    InvalidEncodingError(String),
+
     #[error("Artifact: {0}")]
     ArtifactError(String),
 
@@ -24,6 +42,15 @@ pub enum TokenizeError {
     #[error("Serde: {0}")]
     SerdeError(#[from] serde_json::Error),
 
+    #[error("Toml: {0}")]
+    TomlError(#[from] toml::de::Error),
+
+    #[error("Toml: {0}")]
+    TomlSerError(#[from] toml::ser::Error),
+
+    #[error("Utf8: {0}")]
+    Utf8Error(#[from] std::str::Utf8Error),
+
     #[error("IO: {0}")]
     IOError(#[from] std::io::Error),
 }
@@ -0,0 +1,73 @@
+use crate::error::TokenizeError;
+use unicode_segmentation::UnicodeSegmentation;
+
+//***********************************************
+//            Fixed Width Tokenizer
+//   columns of declared widths in graphemes,
+//   e.g. "10,5,20", text past the last column
+//   is one more column when not empty
+//***********************************************
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedWidthTokenizer {
+    widths: Vec<usize>,
+}
+
+impl FixedWidthTokenizer {
+    pub fn new(widths: Vec<usize>) -> Self {
+        Self { widths }
+    }
+
+    // parse comma separated widths, e.g. "10,5,20"
+    pub fn from_param(param: &str) -> Result<Self, TokenizeError> {
+        let widths = param
+            .split(',')
+            .map(|w| w.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TokenizeError::InvalidTokenizerError(format!("FixedWidth widths {param:?}: {e}")))?;
+        Ok(Self::new(widths))
+    }
+
+    // references into `text`, a column past the end of `text` is empty
+    pub fn ref_words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        // byte offset of each grapheme, and of the end of the text
+        let bounds = text
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .collect::<Vec<_>>();
+        let last = bounds.len() - 1;
+        let mut columns = Vec::with_capacity(self.widths.len() + 1);
+        let mut start = 0;
+        for width in &self.widths {
+            let end = (start + width).min(last);
+            columns.push(&text[bounds[start]..bounds[end]]);
+            start = end;
+        }
+        if start < last {
+            columns.push(&text[bounds[start]..]);
+        }
+        columns.into_iter()
+    }
+
+    pub fn words(&self, text: &str) -> Vec<String> {
+        self.ref_words(text).map(String::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        let toker = FixedWidthTokenizer::from_param("3,2,4").unwrap();
+        assert_eq!(toker.words("abcdefghijk"), vec!["abc", "de", "fghi", "jk"]);
+        assert_eq!(toker.words("abcdefghi"), vec!["abc", "de", "fghi"]);
+        assert_eq!(toker.words("abcd"), vec!["abc", "d", ""]);
+        assert_eq!(toker.words(""), vec!["", "", ""]);
+
+        // widths count graphemes
+        assert_eq!(toker.words("ñe\u{301}x🍺🍕y"), vec!["ñe\u{301}x", "🍺🍕", "y"]);
+        assert!(FixedWidthTokenizer::from_param("3,x").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use unicode_segmentation::UnicodeSegmentation;
 
 #[macro_use]
@@ -11,12 +12,41 @@ use error::TokenizeError;
 pub mod tokenizer;
 use tokenizer::Tokenizer;
 
+pub mod token;
+pub use token::{Token, TokenKind};
+
 //================================================
 // TokenizationSpec describes a rule set for
 // transforming text, tokenizing, and filtering
 //================================================
 pub mod tokenization_spec;
-pub use tokenization_spec::TokenizationSpec;
+pub use tokenization_spec::{SpecFormat, TokenizationSpec};
+
+//================================================
+// Stages are the ordered token normalization steps
+//================================================
+pub mod stage;
+use stage::StageConfig;
+pub use stage::{NormalizationForm, Stage};
+
+//================================================
+// WordLists are the stopwords and keepwords
+//================================================
+pub mod wordlist;
+pub use wordlist::WordList;
+
+//================================================
+// NGrams turn the final tokens into word n-grams
+// or char shingles
+//================================================
+pub mod ngram;
+pub use ngram::NGrams;
+
+//================================================
+// Encoding is the policy for non UTF-8 input
+//================================================
+pub mod encoding;
+pub use encoding::Encoding;
 
 //================================================
 //            Implemented Tokenizers
@@ -28,17 +58,28 @@ pub enum TokenizerType {
     UnicodeWord,
     Whitespace,
     RegexBoundary,
+    RegexMatch,
+    RegexSplit,
+    Csv,
+    Grapheme,
+    FixedWidth,
 }
 
 //================================================
 // A Tokenizer holds a TokenizationConfig which is
 // built from fields in the TokenizationSpec
+//
+// `stages` is the ordered list of token stages:
+// trimmed_tokens, filter_tokens_re, the spec
+// `stages`, then stopwords and keepwords
+//
+// `ngrams` combines the tokens left after `stages`
 //================================================
 #[derive(Clone, Debug)]
 pub struct TokenizationConfig {
     downcase_text: bool,
-    trimmed_tokens: bool,
-    filter_tokens_re: Option<Regex>,
+    stages: Vec<StageConfig>,
+    ngrams: Option<NGrams>,
 }
 
 //================================================
@@ -50,35 +91,128 @@ pub fn tokenizer_from_spec(spec: &TokenizationSpec) -> Result<Tokenizer, Tokeniz
     //
     // 1. SplitStr supplies `param` as the String pattern to split()
     // 2. RegexBoundary interprets `param` as additional boundary chars
+    // 3. RegexMatch, RegexSplit require `param` as the Regex pattern
+    // 4. Csv interprets `param` as the field delimiter (default ",")
+    // 5. FixedWidth requires `param` as the column widths, e.g. "10,5,20"
     let param = spec.tokenizer_init_param.clone();
 
+    // compile the required Regex `param`
+    let pattern = |param: Option<String>| -> Result<Regex, TokenizeError> {
+        let param = param.ok_or_else(|| {
+            TokenizeError::InvalidTokenizerError(format!("{:?} requires a pattern", spec.tokenizer_type))
+        })?;
+        compile_regex("tokenizer_init_param", &param)
+    };
+
     let word_tokenizer = match spec.tokenizer_type {
         TokenizerType::SplitStr => WordTokenizer::SplitStr(SplitStrTokenizer::new(param)),
         TokenizerType::UnicodeSegment => WordTokenizer::UnicodeSegment(UnicodeSegmentTokenizer),
         TokenizerType::UnicodeWord => WordTokenizer::UnicodeWord(UnicodeWordTokenizer),
         TokenizerType::Whitespace => WordTokenizer::Whitespace(WhitespaceTokenizer),
         TokenizerType::RegexBoundary => WordTokenizer::RegexBoundary(RegexBoundaryTokenizer::new(param)),
+        TokenizerType::RegexMatch => WordTokenizer::RegexMatch(RegexMatchTokenizer::new(pattern(param)?)),
+        TokenizerType::RegexSplit => WordTokenizer::RegexSplit(RegexSplitTokenizer::new(pattern(param)?)),
+        TokenizerType::Csv => match param.as_deref() {
+            Some("") => return Err(TokenizeError::InvalidTokenizerError("Csv delimiter is empty".into())),
+            _ => WordTokenizer::Csv(CsvTokenizer::new(param)),
+        },
+        TokenizerType::Grapheme => WordTokenizer::Grapheme(GraphemeTokenizer),
+        TokenizerType::FixedWidth => match param.as_deref() {
+            Some(widths) => WordTokenizer::FixedWidth(FixedWidthTokenizer::from_param(widths)?),
+            None => {
+                return Err(TokenizeError::InvalidTokenizerError(
+                    "FixedWidth requires widths".into(),
+                ))
+            }
+        },
     };
 
+    // the legacy trim and filter fields run ahead of the spec `stages`
+    let mut stages = vec![];
+    if spec.trimmed_tokens {
+        stages.push(StageConfig::Trim);
+    }
+    if let Some(re) = &spec.filter_tokens_re {
+        stages.push(StageConfig::Filter(compile_regex("filter_tokens_re", re)?));
+    }
+    for (i, stage) in spec.stages.iter().enumerate() {
+        stages.push(StageConfig::new(stage, &format!("stages[{i}]"))?);
+    }
+
+    // stopwords and keepwords filter the final tokens, matching the downcase setting
+    if let Some(words) = &spec.stopwords {
+        stages.push(StageConfig::Stopwords(words.load("stopwords", spec.downcase_text)?));
+    }
+    if let Some(words) = &spec.keepwords {
+        stages.push(StageConfig::Keepwords(words.load("keepwords", spec.downcase_text)?));
+    }
+
+    if let Some(ngrams) = &spec.ngrams {
+        ngrams.validate()?;
+    }
+
     // build a Tokenizer from the `config` and instantiated WordTokenizer
     let config = TokenizationConfig {
         downcase_text: spec.downcase_text,
-        trimmed_tokens: spec.trimmed_tokens,
-        filter_tokens_re: spec.filter_tokens_re.as_ref().map(|re| Regex::new(re).unwrap()),
+        stages,
+        ngrams: spec.ngrams.clone(),
     };
     Ok(Tokenizer::Spec(config, word_tokenizer))
 }
 
+//================================================
+// compiles a Regex from the TokenizationSpec `field`
+// an invalid pattern returns InvalidRegexError with
+// the position reported by the regex parser
+//================================================
+pub fn compile_regex(field: &str, pattern: &str) -> Result<Regex, TokenizeError> {
+    Regex::new(pattern).map_err(|e| {
+        let (position, reason) = match regex_syntax::Parser::new().parse(pattern) {
+            Err(regex_syntax::Error::Parse(e)) => (Some(e.span().start.offset), e.kind().to_string()),
+            Err(regex_syntax::Error::Translate(e)) => (Some(e.span().start.offset), e.kind().to_string()),
+            _ => (None, e.to_string()),
+        };
+        TokenizeError::InvalidRegexError {
+            field: field.into(),
+            pattern: pattern.into(),
+            position,
+            reason,
+        }
+    })
+}
+
 //================================================
 //         WordTokenizer, WordTokens trait
-// WordTokenizer: an object with a words() method
-//                which returns a list of String
+// WordTokenizer: an object with a ref_words() method
+//                which returns an iterator of &str
+//                referencing the input text
 //================================================
 #[enum_delegate::register]
 trait WordTokens {
     // default implementation is Whitespace
-    fn words(&self, text: &str) -> Vec<String> {
-        text.split_whitespace().map(String::from).collect()
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(text.split_whitespace())
+    }
+
+    // the words and the boundaries between them, for spans(), when ref_words() drops
+    // the boundaries (e.g. RegexBoundary), the default is ref_words()
+    fn ref_segments<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        self.ref_words(text)
+    }
+
+    // the token text of a word returned by ref_words() (e.g. CSV unquoting)
+    fn token<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(word)
+    }
+
+    // false when `text` ends inside a multi-line record (e.g. a quoted CSV field)
+    fn is_complete(&self, _text: &str) -> bool {
+        true
+    }
+
+    // classification of a word returned by ref_words() or ref_segments()
+    fn kind(&self, _word: &str) -> TokenKind {
+        TokenKind::Word
     }
 }
 
@@ -90,6 +224,11 @@ pub enum WordTokenizer {
     UnicodeWord(UnicodeWordTokenizer),
     Whitespace(WhitespaceTokenizer),
     RegexBoundary(RegexBoundaryTokenizer),
+    RegexMatch(RegexMatchTokenizer),
+    RegexSplit(RegexSplitTokenizer),
+    Csv(CsvTokenizer),
+    Grapheme(GraphemeTokenizer),
+    FixedWidth(FixedWidthTokenizer),
 }
 
 // *********************************************************
@@ -103,8 +242,8 @@ pub enum WordTokenizer {
 pub mod splitstr;
 use splitstr::SplitStrTokenizer;
 impl WordTokens for SplitStrTokenizer {
-    fn words(&self, text: &str) -> Vec<String> {
-        self.words(text)
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.ref_words(text))
     }
 }
 
@@ -115,8 +254,16 @@ impl WordTokens for SplitStrTokenizer {
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct UnicodeSegmentTokenizer;
 impl WordTokens for UnicodeSegmentTokenizer {
-    fn words(&self, text: &str) -> Vec<String> {
-        text.split_word_bounds().map(String::from).collect()
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(text.split_word_bounds())
+    }
+
+    // segments without an alphanumeric char are boundaries (as in unicode_words())
+    fn kind(&self, word: &str) -> TokenKind {
+        match word.chars().any(char::is_alphanumeric) {
+            true => TokenKind::Word,
+            false => TokenKind::Boundary,
+        }
     }
 }
 
@@ -127,8 +274,8 @@ impl WordTokens for UnicodeSegmentTokenizer {
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct UnicodeWordTokenizer;
 impl WordTokens for UnicodeWordTokenizer {
-    fn words(&self, text: &str) -> Vec<String> {
-        text.unicode_words().map(String::from).collect()
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(text.unicode_words())
     }
 }
 
@@ -149,8 +296,93 @@ impl WordTokens for WhitespaceTokenizer {
 pub mod regexboundary;
 use regexboundary::RegexBoundaryTokenizer;
 impl WordTokens for RegexBoundaryTokenizer {
-    fn words(&self, text: &str) -> Vec<String> {
-        self.words(text)
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.iter(text).filter_map(|t| match t {
+            regexboundary::Token::T(s) => Some(s),
+            regexboundary::Token::B(_) => None,
+        }))
+    }
+
+    fn ref_segments<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.iter(text).map(|t| match t {
+            regexboundary::Token::T(s) | regexboundary::Token::B(s) => s,
+        }))
+    }
+
+    // a segment is a run of boundary or non-boundary chars, its first char decides
+    fn kind(&self, word: &str) -> TokenKind {
+        match word.chars().next().is_some_and(|c| self.boundary_predicate(c)) {
+            true => TokenKind::Boundary,
+            false => TokenKind::Word,
+        }
+    }
+}
+
+//================================================
+//             Regex Match Tokenizer
+//           TokenizerType::RegexMatch
+//================================================
+pub mod regexmatch;
+use regexmatch::RegexMatchTokenizer;
+impl WordTokens for RegexMatchTokenizer {
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.ref_words(text))
+    }
+}
+
+//================================================
+//             Regex Split Tokenizer
+//           TokenizerType::RegexSplit
+//================================================
+pub mod regexsplit;
+use regexsplit::RegexSplitTokenizer;
+impl WordTokens for RegexSplitTokenizer {
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.ref_words(text))
+    }
+}
+
+//================================================
+//                 Csv Tokenizer
+//               TokenizerType::Csv
+//================================================
+pub mod csv;
+use csv::CsvTokenizer;
+impl WordTokens for CsvTokenizer {
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.ref_words(text))
+    }
+
+    fn token<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        csv::unquote(word)
+    }
+
+    fn is_complete(&self, text: &str) -> bool {
+        self.is_complete(text)
+    }
+}
+
+//================================================
+//              Grapheme Tokenizer
+//            TokenizerType::Grapheme
+//================================================
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphemeTokenizer;
+impl WordTokens for GraphemeTokenizer {
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(text.graphemes(true))
+    }
+}
+
+//================================================
+//             Fixed Width Tokenizer
+//           TokenizerType::FixedWidth
+//================================================
+pub mod fixedwidth;
+use fixedwidth::FixedWidthTokenizer;
+impl WordTokens for FixedWidthTokenizer {
+    fn ref_words<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        Box::new(self.ref_words(text))
     }
 }
 
@@ -164,12 +396,122 @@ mod tests {
     fn normal_types() {
         is_normal::<TokenizerType>();
         is_normal::<TokenizationConfig>();
+        is_normal::<Stage>();
+        is_normal::<NGrams>();
         is_normal::<WordTokenizer>();
         is_normal::<SplitStrTokenizer>();
         is_normal::<UnicodeSegmentTokenizer>();
         is_normal::<UnicodeWordTokenizer>();
         is_normal::<WhitespaceTokenizer>();
         is_normal::<RegexBoundaryTokenizer>();
-        is_normal::<Tokenizer>()
+        is_normal::<RegexMatchTokenizer>();
+        is_normal::<RegexSplitTokenizer>();
+        is_normal::<CsvTokenizer>();
+        is_normal::<GraphemeTokenizer>();
+        is_normal::<FixedWidthTokenizer>();
+        is_normal::<Tokenizer>();
+        is_normal::<Token>();
+        is_normal::<TokenKind>()
+    }
+
+    #[test]
+    fn regex_tokenizers_from_spec() {
+        let mut spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::RegexMatch,
+            tokenizer_init_param: Some(r"\d+\.\d+|\w+".into()),
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens("v1.5 of x"), vec!["v1", "5", "of", "x"]);
+
+        spec.tokenizer_type = TokenizerType::RegexSplit;
+        spec.tokenizer_init_param = Some(r"\s*[,;]\s*".into());
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens("a , b;c"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn regex_tokenizers_invalid_pattern() {
+        for tokenizer_type in [TokenizerType::RegexMatch, TokenizerType::RegexSplit] {
+            let mut spec = TokenizationSpec {
+                tokenizer_type,
+                ..Default::default()
+            };
+            assert!(matches!(
+                tokenizer_from_spec(&spec),
+                Err(TokenizeError::InvalidTokenizerError(_))
+            ));
+
+            spec.tokenizer_init_param = Some("ab(c".into());
+            match tokenizer_from_spec(&spec) {
+                Err(TokenizeError::InvalidRegexError {
+                    field,
+                    pattern,
+                    position,
+                    ..
+                }) => {
+                    assert_eq!(field, "tokenizer_init_param");
+                    assert_eq!(pattern, "ab(c");
+                    assert_eq!(position, Some(2));
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn csv_from_spec() {
+        let mut spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::Csv,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(
+            tokenizer.tokens(r#"1,"Smith, John","say ""hi""""#),
+            vec!["1", "Smith, John", r#"say "hi""#]
+        );
+
+        spec.tokenizer_init_param = Some("".into());
+        assert!(matches!(
+            tokenizer_from_spec(&spec),
+            Err(TokenizeError::InvalidTokenizerError(_))
+        ));
+    }
+
+    #[test]
+    fn fixed_width_from_spec() {
+        let mut spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::FixedWidth,
+            ..Default::default()
+        };
+        assert!(matches!(
+            tokenizer_from_spec(&spec),
+            Err(TokenizeError::InvalidTokenizerError(_))
+        ));
+
+        spec.tokenizer_init_param = Some("4,3".into());
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens("0042ABCrest"), vec!["0042", "ABC", "rest"]);
+
+        spec.tokenizer_type = TokenizerType::Grapheme;
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens("ae\u{301}🍺"), vec!["a", "e\u{301}", "🍺"]);
+    }
+
+    #[test]
+    fn invalid_filter_regex() {
+        let spec = TokenizationSpec {
+            filter_tokens_re: Some(r"^\p{Bogus}$".into()),
+            ..Default::default()
+        };
+        let err = tokenizer_from_spec(&spec).unwrap_err();
+        assert!(matches!(
+            &err,
+            TokenizeError::InvalidRegexError { field, position: Some(1), .. } if field == "filter_tokens_re"
+        ));
+        assert_eq!(
+            err.to_string(),
+            r#"InvalidRegex: filter_tokens_re "^\\p{Bogus}$" at position 1: Unicode property not found"#
+        );
     }
 }
@@ -0,0 +1,207 @@
+use crate::error::TokenizeError;
+use crate::token::{Token, TokenKind};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+//================================================
+// NGrams replaces the final tokens with n-grams
+// for every n in min..=max
+//
+// word n-grams join `n` consecutive tokens with `joiner`
+// char n-grams slide an `n` char window over each token,
+// when `pad` is set the token is padded with n-1 pad chars
+// on each side (q-gram padding)
+//================================================
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct NGrams {
+    pub min: usize,
+    pub max: usize,
+    pub joiner: String,
+    pub chars: bool,
+    pub pad: Option<char>,
+}
+impl Default for NGrams {
+    fn default() -> Self {
+        NGrams {
+            min: 1,
+            max: 1,
+            joiner: " ".into(),
+            chars: false,
+            pad: None,
+        }
+    }
+}
+
+// "N" or "N-M"
+impl FromStr for NGrams {
+    type Err = TokenizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let n = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| TokenizeError::InvalidTokenizerError(format!("ngrams range {s:?}")))
+        };
+        let (min, max) = match s.split_once('-') {
+            Some((min, max)) => (n(min)?, n(max)?),
+            None => (n(s)?, n(s)?),
+        };
+        Ok(NGrams {
+            min,
+            max,
+            ..Default::default()
+        })
+    }
+}
+
+impl NGrams {
+    pub(crate) fn validate(&self) -> Result<(), TokenizeError> {
+        match self.min > 0 && self.min <= self.max {
+            true => Ok(()),
+            false => Err(TokenizeError::InvalidTokenizerError(format!(
+                "ngrams range {}-{}",
+                self.min, self.max
+            ))),
+        }
+    }
+
+    // (first, last) token indices of every word n-gram
+    fn windows(&self, count: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.min..=self.max).flat_map(move |n| (0..(count + 1).saturating_sub(n)).map(move |i| (i, i + n - 1)))
+    }
+
+    // char n-grams of a token
+    fn char_grams(&self, token: &str) -> Vec<String> {
+        (self.min..=self.max)
+            .flat_map(|n| {
+                let pad = self.pad.map(|c| vec![c; n - 1]).unwrap_or_default();
+                let chars = pad
+                    .iter()
+                    .chain(token.chars().collect::<Vec<_>>().iter())
+                    .chain(pad.iter())
+                    .copied()
+                    .collect::<Vec<_>>();
+                chars.windows(n).map(String::from_iter).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub(crate) fn apply<'a>(&self, tokens: Vec<Cow<'a, str>>) -> Vec<Cow<'a, str>> {
+        match self.chars {
+            true => tokens.iter().flat_map(|t| self.char_grams(t)).map(Cow::Owned).collect(),
+            false => self
+                .windows(tokens.len())
+                .map(|(i, j)| match i == j {
+                    true => tokens[i].clone(),
+                    false => Cow::Owned(tokens[i..=j].join(&self.joiner)),
+                })
+                .collect(),
+        }
+    }
+
+    // word n-grams span from the first to the last token, char n-grams keep the token span
+    pub(crate) fn apply_spans(&self, tokens: Vec<Token>) -> Vec<Token> {
+        match self.chars {
+            true => tokens
+                .iter()
+                .flat_map(|t| {
+                    self.char_grams(&t.text).into_iter().map(|text| Token {
+                        text,
+                        kind: TokenKind::Word,
+                        ..t.clone()
+                    })
+                })
+                .collect(),
+            false => self
+                .windows(tokens.len())
+                .map(|(i, j)| match i == j {
+                    true => tokens[i].clone(),
+                    false => Token {
+                        text: tokens[i..=j]
+                            .iter()
+                            .map(|t| t.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(&self.joiner),
+                        kind: TokenKind::Word,
+                        byte_start: tokens[i].byte_start,
+                        byte_end: tokens[j].byte_end,
+                        char_start: tokens[i].char_start,
+                        char_end: tokens[j].char_end,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cows(words: &[&'static str]) -> Vec<Cow<'static, str>> {
+        words.iter().map(|w| Cow::Borrowed(*w)).collect()
+    }
+
+    #[test]
+    fn from_str() {
+        let ngrams = "1-3".parse::<NGrams>().unwrap();
+        assert_eq!((ngrams.min, ngrams.max), (1, 3));
+        let ngrams = "2".parse::<NGrams>().unwrap();
+        assert_eq!((ngrams.min, ngrams.max), (2, 2));
+        assert!("x-2".parse::<NGrams>().is_err());
+        assert!("3-1".parse::<NGrams>().unwrap().validate().is_err());
+        assert!("0".parse::<NGrams>().unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn word_ngrams() {
+        let ngrams = NGrams {
+            joiner: "_".into(),
+            ..NGrams::from_str("1-3").unwrap()
+        };
+        assert_eq!(
+            ngrams.apply(cows(&["a", "b", "c"])),
+            vec!["a", "b", "c", "a_b", "b_c", "a_b_c"]
+        );
+        assert_eq!(ngrams.apply(cows(&["a"])), vec!["a"]);
+        assert_eq!(ngrams.apply(cows(&[])), Vec::<Cow<str>>::new());
+    }
+
+    #[test]
+    fn char_ngrams() {
+        let mut ngrams = NGrams {
+            chars: true,
+            ..NGrams::from_str("2-3").unwrap()
+        };
+        assert_eq!(ngrams.apply(cows(&["abc", "d"])), vec!["ab", "bc", "abc"]);
+
+        ngrams.pad = Some('_');
+        ngrams.min = 3;
+        assert_eq!(ngrams.apply(cows(&["ab"])), vec!["__a", "_ab", "ab_", "b__"]);
+    }
+
+    #[test]
+    fn word_ngram_spans() {
+        let token = |text: &str, start: usize| Token {
+            text: text.into(),
+            kind: TokenKind::Word,
+            byte_start: start,
+            byte_end: start + text.len(),
+            char_start: start,
+            char_end: start + text.len(),
+        };
+        // "ab cd  e"
+        let ngrams = NGrams::from_str("2").unwrap();
+        let spans = ngrams.apply_spans(vec![token("ab", 0), token("cd", 3), token("e", 7)]);
+        assert_eq!(
+            spans.iter().map(|t| t.text.as_str()).collect::<Vec<_>>(),
+            vec!["ab cd", "cd e"]
+        );
+        assert_eq!(
+            spans.iter().map(|t| (t.byte_start, t.byte_end)).collect::<Vec<_>>(),
+            vec![(0, 5), (3, 8)]
+        );
+    }
+}
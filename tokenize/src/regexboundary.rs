@@ -55,46 +55,38 @@ impl RegexBoundaryTokenizer {
         !self.excluded_boundary_chars.contains(c) && REGEX_BOUNDARY_CHAR.is_match(&("X".to_string() + &c.to_string()))
     }
 
+    // return a lazy iterator of enum Token<'a> of type B or T (Boundary or Token)
+    // each Token holds a reference into the input string
+    //
+    // tokens alternate between maximal runs of boundary and non-boundary chars
+    pub fn iter<'s, 'a: 's>(&'s self, input: &'a str) -> impl Iterator<Item = Token<'a>> + 's {
+        let mut rest = input;
+        std::iter::from_fn(move || {
+            let first = rest.chars().next()?;
+            let boundary = self.boundary_predicate(first);
+
+            // str references are being returned (indexed by utf8 units)
+            let end = rest[first.len_utf8()..]
+                .char_indices()
+                .find(|(_, c)| self.boundary_predicate(*c) != boundary)
+                .map_or(rest.len(), |(i, _)| i + first.len_utf8());
+
+            let (s, tail) = rest.split_at(end);
+            rest = tail;
+            Some(match boundary {
+                true => Token::B(s),
+                false => Token::T(s),
+            })
+        })
+    }
+
     // return a list of enum Token<'a> of type B or T (Boundary or Token)
     // each Token holds a reference into the input string
     //
     // joining the contents of the list would reproduce the input
     //    assert_eq!(Token::joined(&tokens), input);
     pub fn tokens<'a>(&self, input: &'a str) -> Vec<Token<'a>> {
-        let mut i = 0;
-        let mut b = 0;
-        let mut t = 0;
-        let mut tokens = vec![];
-
-        for c in input.chars() {
-            // str references are being returned (indexed by utf8 units)
-            let c_len = c.len_utf8();
-
-            if self.boundary_predicate(c) {
-                // finalize previous token if needed
-                if i > t {
-                    tokens.push(Token::T(&input[t..i]));
-                }
-                i += c_len;
-                t = i;
-            } else {
-                // finalize previous boundary if needed
-                if i > b {
-                    tokens.push(Token::B(&input[b..i]));
-                }
-                i += c_len;
-                b = i;
-            }
-        }
-
-        // finalize the token which was last being processed
-        if i > b {
-            tokens.push(Token::B(&input[b..i]));
-        } else if i > t {
-            tokens.push(Token::T(&input[t..i]));
-        }
-
-        tokens
+        self.iter(input).collect()
     }
 
     // returns a string list of all tokens
@@ -104,8 +96,7 @@ impl RegexBoundaryTokenizer {
 
     // filters the tokens on Token::T() and returns a reference list
     pub fn ref_words<'a>(&self, text: &'a str) -> Vec<&'a str> {
-        self.tokens(text)
-            .into_iter()
+        self.iter(text)
             .filter(|t| matches!(t, Token::T(_)))
             .map(|t| t.str_value())
             .collect()
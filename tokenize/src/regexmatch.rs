@@ -0,0 +1,48 @@
+use regex::Regex;
+
+//***********************************************
+//             RegexMatch Tokenizer
+//     tokens are the matches of a pattern
+//***********************************************
+#[derive(Clone, Debug)]
+pub struct RegexMatchTokenizer {
+    re: Regex,
+}
+
+// Regex has no PartialEq, tokenizers compare equal on their pattern
+impl PartialEq for RegexMatchTokenizer {
+    fn eq(&self, other: &Self) -> bool {
+        self.re.as_str() == other.re.as_str()
+    }
+}
+impl Eq for RegexMatchTokenizer {}
+
+impl RegexMatchTokenizer {
+    pub fn new(re: Regex) -> Self {
+        Self { re }
+    }
+
+    // references into `text` of every non-overlapping match
+    pub fn ref_words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.re.find_iter(text).map(|m| m.as_str())
+    }
+
+    pub fn words(&self, text: &str) -> Vec<String> {
+        self.ref_words(text).map(String::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let toker = RegexMatchTokenizer::new(Regex::new(r"\d+\.\d+|\w+").unwrap());
+        assert_eq!(
+            toker.words("pi is 3.14, e=2.71!"),
+            vec!["pi", "is", "3.14", "e", "2.71"]
+        );
+        assert_eq!(toker.words("..."), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,45 @@
+use regex::Regex;
+
+//***********************************************
+//             RegexSplit Tokenizer
+//   tokens are the text between pattern matches
+//***********************************************
+#[derive(Clone, Debug)]
+pub struct RegexSplitTokenizer {
+    re: Regex,
+}
+
+// Regex has no PartialEq, tokenizers compare equal on their pattern
+impl PartialEq for RegexSplitTokenizer {
+    fn eq(&self, other: &Self) -> bool {
+        self.re.as_str() == other.re.as_str()
+    }
+}
+impl Eq for RegexSplitTokenizer {}
+
+impl RegexSplitTokenizer {
+    pub fn new(re: Regex) -> Self {
+        Self { re }
+    }
+
+    // references into `text` split on the pattern
+    pub fn ref_words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.re.split(text)
+    }
+
+    pub fn words(&self, text: &str) -> Vec<String> {
+        self.ref_words(text).map(String::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits() {
+        let toker = RegexSplitTokenizer::new(Regex::new(r"\s*[,;]\s*").unwrap());
+        assert_eq!(toker.words("a , b;c ;; d"), vec!["a", "b", "c", "", "d"]);
+        assert_eq!(toker.words(""), vec![""]);
+    }
+}
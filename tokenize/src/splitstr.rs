@@ -13,7 +13,12 @@ impl SplitStrTokenizer {
         }
     }
 
+    // references into `text` split on the pattern
+    pub fn ref_words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        text.split(self.split_pattern.as_str())
+    }
+
     pub fn words(&self, text: &str) -> Vec<String> {
-        text.split(&self.split_pattern).map(String::from).collect()
+        self.ref_words(text).map(String::from).collect()
     }
 }
@@ -0,0 +1,240 @@
+use crate::compile_regex;
+use crate::error::TokenizeError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use unicode_normalization::{char::is_combining_mark, is_nfc, is_nfd, is_nfkc, is_nfkd, UnicodeNormalization};
+
+//================================================
+// Unicode normalization forms
+//================================================
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormalizationForm {
+    NFC,
+    NFD,
+    NFKC,
+    NFKD,
+}
+
+//================================================
+// A Stage is one step of token normalization
+// applied in order to every token, e.g.
+//    "stages": [{"Normalize": "NFKC"}, "FoldAccents", "CaseFold",
+//               "StripPunctuation", {"Length": {"min": 2, "max": null}}]
+//================================================
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    // Unicode normalization
+    Normalize(NormalizationForm),
+    // remove diacritics (naïve => naive)
+    FoldAccents,
+    // to_lowercase()
+    Downcase,
+    // full Unicode case folding (Straße => strasse)
+    CaseFold,
+    // whitespace trim
+    Trim,
+    // remove punctuation at the token edges
+    StripPunctuation,
+    // discard tokens with fewer than `min` or more than `max` chars
+    Length { min: Option<usize>, max: Option<usize> },
+    // discard tokens matching a RE
+    Filter(String),
+}
+
+//================================================
+// A Stage built for a Tokenizer, Regex compiled
+//================================================
+#[derive(Clone, Debug)]
+pub(crate) enum StageConfig {
+    Normalize(NormalizationForm),
+    FoldAccents,
+    Downcase,
+    CaseFold,
+    Trim,
+    StripPunctuation,
+    Length(Option<usize>, Option<usize>),
+    Filter(Regex),
+    // discard tokens in the set
+    Stopwords(HashSet<String>),
+    // discard tokens not in the set
+    Keepwords(HashSet<String>),
+}
+impl StageConfig {
+    // `field` names the TokenizationSpec field the stage came from (error reporting)
+    pub(crate) fn new(stage: &Stage, field: &str) -> Result<Self, TokenizeError> {
+        Ok(match stage {
+            Stage::Normalize(form) => Self::Normalize(*form),
+            Stage::FoldAccents => Self::FoldAccents,
+            Stage::Downcase => Self::Downcase,
+            Stage::CaseFold => Self::CaseFold,
+            Stage::Trim => Self::Trim,
+            Stage::StripPunctuation => Self::StripPunctuation,
+            Stage::Length { min, max } => Self::Length(*min, *max),
+            Stage::Filter(re) => Self::Filter(compile_regex(field, re)?),
+        })
+    }
+
+    // transform a token, None discards it
+    //
+    // a Cow::Borrowed token stays borrowed unless the stage changes the text
+    pub(crate) fn apply<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        lazy_static! {
+            static ref LEADING_PUNCT: Regex = Regex::new(r"^\p{P}+").unwrap();
+            static ref TRAILING_PUNCT: Regex = Regex::new(r"\p{P}+$").unwrap();
+        }
+
+        match self {
+            Self::Normalize(form) => {
+                let normalized = match form {
+                    NormalizationForm::NFC => is_nfc(&token),
+                    NormalizationForm::NFD => is_nfd(&token),
+                    NormalizationForm::NFKC => is_nfkc(&token),
+                    NormalizationForm::NFKD => is_nfkd(&token),
+                };
+                Some(match normalized {
+                    true => token,
+                    false => Cow::Owned(match form {
+                        NormalizationForm::NFC => token.nfc().collect(),
+                        NormalizationForm::NFD => token.nfd().collect(),
+                        NormalizationForm::NFKC => token.nfkc().collect(),
+                        NormalizationForm::NFKD => token.nfkd().collect(),
+                    }),
+                })
+            }
+            Self::FoldAccents => Some(match token.is_ascii() {
+                true => token,
+                false => {
+                    let folded = token.nfd().filter(|c| !is_combining_mark(*c)).nfc().collect::<String>();
+                    match folded == token {
+                        true => token,
+                        false => Cow::Owned(folded),
+                    }
+                }
+            }),
+            Self::Downcase => Some(match token {
+                Cow::Borrowed(s) => crate::tokenizer::downcase(s),
+                Cow::Owned(s) => Cow::Owned(s.to_lowercase()),
+            }),
+            Self::CaseFold => {
+                let folded = caseless::default_case_fold_str(&token);
+                Some(match folded == token {
+                    true => token,
+                    false => Cow::Owned(folded),
+                })
+            }
+            Self::Trim => Some(trim_with(token, |s| s.trim())),
+            Self::StripPunctuation => Some(trim_with(token, |s| {
+                let s = &s[LEADING_PUNCT.find(s).map_or(0, |m| m.end())..];
+                &s[..TRAILING_PUNCT.find(s).map_or(s.len(), |m| m.start())]
+            })),
+            Self::Length(min, max) => {
+                let n = token.chars().count();
+                match min.is_some_and(|min| n < min) || max.is_some_and(|max| n > max) {
+                    true => None,
+                    false => Some(token),
+                }
+            }
+            Self::Filter(re) => match re.is_match(&token) {
+                true => None,
+                false => Some(token),
+            },
+            Self::Stopwords(words) => match words.contains(token.as_ref()) {
+                true => None,
+                false => Some(token),
+            },
+            Self::Keepwords(words) => match words.contains(token.as_ref()) {
+                true => Some(token),
+                false => None,
+            },
+        }
+    }
+}
+
+// narrow a token to a sub-slice, borrowed tokens stay borrowed
+fn trim_with<'a>(token: Cow<'a, str>, trim: impl Fn(&str) -> &str) -> Cow<'a, str> {
+    match token {
+        Cow::Borrowed(s) => Cow::Borrowed(trim(s)),
+        Cow::Owned(s) => match trim(&s).len() == s.len() {
+            true => Cow::Owned(s),
+            false => Cow::Owned(trim(&s).into()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(stage: Stage, token: &str) -> Option<Cow<'_, str>> {
+        StageConfig::new(&stage, "stages").unwrap().apply(Cow::Borrowed(token))
+    }
+
+    #[test]
+    fn normalize() {
+        let decomposed = "nai\u{308}ve";
+        assert_eq!(
+            apply(Stage::Normalize(NormalizationForm::NFC), decomposed).unwrap(),
+            "naïve"
+        );
+        assert_eq!(
+            apply(Stage::Normalize(NormalizationForm::NFD), "naïve").unwrap(),
+            decomposed
+        );
+        assert_eq!(apply(Stage::Normalize(NormalizationForm::NFKC), "ﬁ①").unwrap(), "fi1");
+        assert_eq!(
+            apply(Stage::Normalize(NormalizationForm::NFKD), "é").unwrap(),
+            "e\u{301}"
+        );
+        assert!(matches!(
+            apply(Stage::Normalize(NormalizationForm::NFC), "naïve"),
+            Some(Cow::Borrowed(_))
+        ));
+    }
+
+    #[test]
+    fn fold_accents() {
+        assert_eq!(apply(Stage::FoldAccents, "naïve").unwrap(), "naive");
+        assert_eq!(apply(Stage::FoldAccents, "Sinéad Ñandú").unwrap(), "Sinead Nandu");
+        assert!(matches!(apply(Stage::FoldAccents, "plain"), Some(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(apply(Stage::Downcase, "Straße").unwrap(), "straße");
+        assert_eq!(apply(Stage::CaseFold, "Straße").unwrap(), "strasse");
+        assert!(matches!(apply(Stage::CaseFold, "lower"), Some(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(apply(Stage::Trim, "  x y ").unwrap(), "x y");
+        assert_eq!(apply(Stage::StripPunctuation, "«¡Hola!»").unwrap(), "Hola");
+        assert_eq!(apply(Stage::StripPunctuation, "O'Connor,").unwrap(), "O'Connor");
+        assert_eq!(apply(Stage::StripPunctuation, "...").unwrap(), "");
+        assert!(matches!(
+            apply(Stage::StripPunctuation, "(x)"),
+            Some(Cow::Borrowed("x"))
+        ));
+    }
+
+    #[test]
+    fn filters() {
+        let length = || Stage::Length {
+            min: Some(2),
+            max: Some(3),
+        };
+        assert_eq!(apply(length(), "a"), None);
+        assert_eq!(apply(length(), "ab").unwrap(), "ab");
+        assert_eq!(apply(length(), "äöü").unwrap(), "äöü");
+        assert_eq!(apply(length(), "abcd"), None);
+        assert_eq!(apply(Stage::Filter("^x".into()), "xyz"), None);
+        assert_eq!(apply(Stage::Filter("^x".into()), "yz").unwrap(), "yz");
+        assert!(matches!(
+            StageConfig::new(&Stage::Filter("(".into()), "stages"),
+            Err(TokenizeError::InvalidRegexError { .. })
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+//================================================
+// A Token is a tokenizer output along with its
+// position in the input text
+//
+// byte and char offsets always refer to the
+// original input, even when the token text was
+// downcased into a different length
+//================================================
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Word,
+    Boundary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+// byte offset of `part` within `whole`, `part` must be a slice of `whole`
+pub(crate) fn offset_in(whole: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - whole.as_ptr() as usize;
+    debug_assert!(offset + part.len() <= whole.len(), "token is not a slice of the text");
+    offset
+}
+
+//================================================
+// OffsetMap translates byte offsets of a transformed
+// text (e.g. lowercased) back to the original text
+//
+// every byte of a transformed char records the byte
+// range of the original char which produced it
+//================================================
+pub(crate) struct OffsetMap {
+    source: Vec<(usize, usize)>,
+    original_len: usize,
+}
+impl OffsetMap {
+    // `expansion(c)` is the number of chars `c` produces in `transformed`
+    pub(crate) fn new(original: &str, transformed: &str, expansion: impl Fn(char) -> usize) -> Self {
+        let mut source = Vec::with_capacity(transformed.len());
+        let mut transformed_chars = transformed.chars();
+        for (i, c) in original.char_indices() {
+            let range = (i, i + c.len_utf8());
+            for t in transformed_chars.by_ref().take(expansion(c)) {
+                source.extend(std::iter::repeat_n(range, t.len_utf8()));
+            }
+        }
+        debug_assert_eq!(
+            source.len(),
+            transformed.len(),
+            "transformed text is not a char expansion"
+        );
+        Self {
+            source,
+            original_len: original.len(),
+        }
+    }
+
+    // map String::from_utf8_lossy(original) back to `original`,
+    // each U+FFFD maps to the invalid bytes it replaced
+    pub(crate) fn lossy(original: &[u8], lossy: &str) -> Self {
+        let mut source = Vec::with_capacity(lossy.len());
+        let mut start = 0;
+        for chunk in original.utf8_chunks() {
+            for (i, c) in chunk.valid().char_indices() {
+                source.extend(std::iter::repeat_n((start + i, start + i + c.len_utf8()), c.len_utf8()));
+            }
+            start += chunk.valid().len();
+            if !chunk.invalid().is_empty() {
+                let range = (start, start + chunk.invalid().len());
+                source.extend(std::iter::repeat_n(range, char::REPLACEMENT_CHARACTER.len_utf8()));
+                start += chunk.invalid().len();
+            }
+        }
+        debug_assert_eq!(source.len(), lossy.len(), "text is not the lossy decoding");
+        Self {
+            source,
+            original_len: original.len(),
+        }
+    }
+
+    // original (start, end) of the transformed byte range [start, end)
+    pub(crate) fn range(&self, start: usize, end: usize) -> (usize, usize) {
+        let original_start = match self.source.get(start) {
+            Some((s, _)) => *s,
+            None => self.original_len,
+        };
+        match end > start {
+            true => (original_start, self.source[end - 1].1),
+            false => (original_start, original_start),
+        }
+    }
+}
+
+//================================================
+// CharCounter converts increasing byte offsets to
+// char offsets without rescanning the text
+//================================================
+pub(crate) struct CharCounter<'a> {
+    text: &'a str,
+    byte: usize,
+    chars: usize,
+}
+impl<'a> CharCounter<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            byte: 0,
+            chars: 0,
+        }
+    }
+
+    pub(crate) fn at(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            self.byte = 0;
+            self.chars = 0;
+        }
+        self.chars += self.text[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_map_expansion() {
+        let original = "aİb";
+        let lowered = original.to_lowercase();
+        assert_eq!(lowered, "ai\u{307}b");
+
+        let map = OffsetMap::new(original, &lowered, |c| c.to_lowercase().count());
+        assert_eq!(map.range(0, 1), (0, 1));
+        assert_eq!(map.range(1, 4), (1, 3));
+        assert_eq!(map.range(4, 5), (3, 4));
+        assert_eq!(map.range(0, 5), (0, 4));
+        assert_eq!(map.range(5, 5), (4, 4));
+    }
+
+    #[test]
+    fn offset_map_lossy() {
+        let original = b"a\xff\xfeb\xc3\xa9";
+        let lossy = String::from_utf8_lossy(original);
+        assert_eq!(lossy, "a\u{fffd}\u{fffd}b\u{e9}");
+
+        let map = OffsetMap::lossy(original, &lossy);
+        assert_eq!(map.range(0, 1), (0, 1));
+        assert_eq!(map.range(1, 4), (1, 2));
+        assert_eq!(map.range(1, 7), (1, 3));
+        assert_eq!(map.range(7, 10), (3, 6));
+    }
+
+    #[test]
+    fn char_counter() {
+        let text = "🍺+🍕 x";
+        let mut counter = CharCounter::new(text);
+        assert_eq!(counter.at(4), 1);
+        assert_eq!(counter.at(9), 3);
+        assert_eq!(counter.at(text.len()), 5);
+        assert_eq!(counter.at(0), 0);
+    }
+}
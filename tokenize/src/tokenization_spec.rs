@@ -1,13 +1,22 @@
-use crate::TokenizerType;
+use crate::error::TokenizeError;
+use crate::{NGrams, Stage, TokenizerType, WordList};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
 
+// missing fields in a spec file take their Default value
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
 pub struct TokenizationSpec {
     pub tokenizer_type: TokenizerType,
     pub tokenizer_init_param: Option<String>,
     pub downcase_text: bool,
     pub trimmed_tokens: bool,
     pub filter_tokens_re: Option<String>,
+    pub stages: Vec<Stage>,
+    pub stopwords: Option<WordList>,
+    pub keepwords: Option<WordList>,
+    pub ngrams: Option<NGrams>,
 }
 impl Default for TokenizationSpec {
     fn default() -> Self {
@@ -17,6 +26,162 @@ impl Default for TokenizationSpec {
             downcase_text: false,
             trimmed_tokens: false,
             filter_tokens_re: None,
+            stages: vec![],
+            stopwords: None,
+            keepwords: None,
+            ngrams: None,
+        }
+    }
+}
+
+//================================================
+// Serialized forms of a TokenizationSpec
+//================================================
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecFormat {
+    Json,
+    Toml,
+}
+impl SpecFormat {
+    // format implied by a file extension (.json, .toml)
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    // JSON specs are objects, anything else is treated as TOML
+    fn detect(text: &str) -> Self {
+        match text.trim_start().starts_with('{') {
+            true => Self::Json,
+            false => Self::Toml,
+        }
+    }
+}
+
+impl TokenizationSpec {
+    // parse `text` in the given format
+    pub fn from_format_str(text: &str, format: SpecFormat) -> Result<Self, TokenizeError> {
+        Ok(match format {
+            SpecFormat::Json => serde_json::from_str(text)?,
+            SpecFormat::Toml => toml::from_str(text)?,
+        })
+    }
+
+    // serialize to the given format
+    pub fn to_format_string(&self, format: SpecFormat) -> Result<String, TokenizeError> {
+        Ok(match format {
+            SpecFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+            SpecFormat::Toml => toml::to_string(self)?,
+        })
+    }
+
+    // read a spec file, the format is taken from the extension or detected from the contents
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, TokenizeError> {
+        let text = std::fs::read_to_string(&path)?;
+        let format = SpecFormat::from_extension(path.as_ref()).unwrap_or_else(|| SpecFormat::detect(&text));
+        Self::from_format_str(&text, format)
+    }
+
+    // write a spec file, the format is taken from the extension (default JSON)
+    pub fn to_path<P: AsRef<Path>>(&self, path: P) -> Result<(), TokenizeError> {
+        let format = SpecFormat::from_extension(path.as_ref()).unwrap_or(SpecFormat::Json);
+        Ok(std::fs::write(path, self.to_format_string(format)?)?)
+    }
+}
+
+// parse a JSON or TOML spec
+impl FromStr for TokenizationSpec {
+    type Err = TokenizeError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::from_format_str(text, SpecFormat::detect(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> TokenizationSpec {
+        TokenizationSpec {
+            tokenizer_type: TokenizerType::SplitStr,
+            tokenizer_init_param: Some(",".into()),
+            downcase_text: true,
+            trimmed_tokens: false,
+            filter_tokens_re: Some(r"^\s*$".into()),
+            stages: vec![
+                Stage::Normalize(crate::NormalizationForm::NFKC),
+                Stage::FoldAccents,
+                Stage::CaseFold,
+                Stage::StripPunctuation,
+                Stage::Length {
+                    min: Some(2),
+                    max: None,
+                },
+                Stage::Filter("^x".into()),
+            ],
+            stopwords: Some(WordList::Builtin("english".into())),
+            keepwords: Some(WordList::Words(vec!["keep".into(), "these".into()])),
+            ngrams: Some(NGrams {
+                min: 2,
+                max: 3,
+                joiner: "_".into(),
+                chars: true,
+                pad: Some('#'),
+            }),
+        }
+    }
+
+    #[test]
+    fn round_trip_formats() {
+        for format in [SpecFormat::Json, SpecFormat::Toml] {
+            let text = spec().to_format_string(format).unwrap();
+            assert_eq!(TokenizationSpec::from_format_str(&text, format).unwrap(), spec());
+            assert_eq!(text.parse::<TokenizationSpec>().unwrap(), spec());
+        }
+    }
+
+    #[test]
+    fn partial_spec_defaults() {
+        let spec: TokenizationSpec = "tokenizer_type = \"UnicodeWord\"\ndowncase_text = true"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.tokenizer_type, TokenizerType::UnicodeWord);
+        assert!(spec.downcase_text);
+        assert_eq!(spec.filter_tokens_re, None);
+
+        let spec: TokenizationSpec = r#"{"trimmed_tokens": true}"#.parse().unwrap();
+        assert_eq!(spec.tokenizer_type, TokenizerType::Whitespace);
+        assert!(spec.trimmed_tokens);
+    }
+
+    #[test]
+    fn invalid_spec() {
+        assert!(matches!(
+            r#"{"tokenizer_type": "Bogus"}"#.parse::<TokenizationSpec>(),
+            Err(TokenizeError::SerdeError(_))
+        ));
+        assert!(matches!(
+            "tokenizer_type = 7".parse::<TokenizationSpec>(),
+            Err(TokenizeError::TomlError(_))
+        ));
+    }
+
+    #[test]
+    fn spec_files() {
+        let dir = std::env::temp_dir();
+        for name in [
+            "tokenize_spec_test.json",
+            "tokenize_spec_test.toml",
+            "tokenize_spec_test.spec",
+        ] {
+            let path = dir.join(format!("{}-{name}", std::process::id()));
+            spec().to_path(&path).unwrap();
+            assert_eq!(TokenizationSpec::from_path(&path).unwrap(), spec());
+            std::fs::remove_file(&path).unwrap();
         }
     }
 }
@@ -1,6 +1,13 @@
+use crate::csv::MAX_RECORD;
+use crate::error::TokenizeError;
+use crate::token::{offset_in, CharCounter, OffsetMap, Token};
+use crate::Encoding;
+use crate::TokenKind;
 use crate::TokenizationConfig;
 use crate::WordTokenizer;
 use crate::WordTokens;
+use std::borrow::Cow;
+use std::io;
 
 //================================================
 // Tokenizer holds an instantiated WordTokenizer
@@ -9,31 +16,693 @@ use crate::WordTokens;
 // text to tokens recipe:
 //    1. downcase the text (true/false)
 //    2. apply WordTokenizer to text
-//    3. whitespace trim tokens (true/false)
-//    4. discard tokens matching a RE
+//    3. apply the ordered token stages
+//       (trim, RE filter, normalization, ...)
+//    4. combine the tokens into n-grams (optional)
 //================================================
 #[derive(Clone, Debug)]
 pub enum Tokenizer {
     Spec(TokenizationConfig, WordTokenizer),
 }
 impl Tokenizer {
-    fn transform_filter(config: &TokenizationConfig, words: Vec<String>) -> Vec<String> {
-        let tokens = match config.trimmed_tokens {
-            true => words.into_iter().map(|t| t.trim().into()).collect(),
-            false => words,
+    // steps 2-3 of the recipe over already downcased text
+    //
+    // yields each token with its anchor: the last borrowed slice of `text`
+    // the token passed through, which locates the token in `text`
+    //
+    // `segments` includes the boundaries ref_words() drops (see WordTokens::ref_segments)
+    fn pipeline<'a>(&'a self, text: &'a str, segments: bool) -> impl Iterator<Item = (Cow<'a, str>, &'a str)> + 'a {
+        let Self::Spec(config, tokenizer) = self;
+        let words = match segments {
+            true => tokenizer.ref_segments(text),
+            false => tokenizer.ref_words(text),
         };
-        match &config.filter_tokens_re {
-            Some(re) => tokens.into_iter().filter(|tok| !re.is_match(tok)).collect(),
+        words.filter_map(move |word| {
+            // a borrowed token, e.g. an unquoted CSV field, is located by its own text
+            let tok = tokenizer.token(word);
+            let anchor = match tok {
+                Cow::Borrowed(s) => s,
+                Cow::Owned(_) => word,
+            };
+            config.stages.iter().try_fold((tok, anchor), |(tok, anchor), stage| {
+                let tok = stage.apply(tok)?;
+                let anchor = match tok {
+                    Cow::Borrowed(s) => s,
+                    Cow::Owned(_) => anchor,
+                };
+                Some((tok, anchor))
+            })
+        })
+    }
+
+    // tokens as references into `text`
+    //
+    // a token is Cow::Owned only when downcasing or a stage changed the token,
+    // otherwise the tokens are Cow::Borrowed slices of `text`, even when downcasing
+    // changed other tokens of the text
+    pub fn tokens_ref<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Cow<'a, str>> + 'a {
+        let Self::Spec(config, _) = self;
+        let tokens: Box<dyn Iterator<Item = Cow<'a, str>> + 'a> = match config.downcase_text {
+            true => match downcase(text) {
+                Cow::Borrowed(text) => Box::new(self.pipeline(text, false).map(|(tok, _)| tok)),
+                // the tokens of the downcased text are collected before it is dropped,
+                // a token downcasing left unchanged is its slice of `text`
+                Cow::Owned(lowered) => {
+                    let offsets = OffsetMap::new(text, &lowered, |c| c.to_lowercase().count());
+                    Box::new(
+                        self.pipeline(&lowered, false)
+                            .map(|(tok, _)| match tok {
+                                Cow::Borrowed(s) => {
+                                    let start = offset_in(&lowered, s);
+                                    let (start, end) = offsets.range(start, start + s.len());
+                                    match &text[start..end] == s {
+                                        true => Cow::Borrowed(&text[start..end]),
+                                        false => Cow::Owned(s.to_string()),
+                                    }
+                                }
+                                Cow::Owned(s) => Cow::Owned(s),
+                            })
+                            .collect::<Vec<_>>()
+                            .into_iter(),
+                    )
+                }
+            },
+            false => Box::new(self.pipeline(text, false).map(|(tok, _)| tok)),
+        };
+        match &config.ngrams {
+            Some(ngrams) => Box::new(ngrams.apply(tokens.collect()).into_iter()),
             None => tokens,
         }
     }
 
     pub fn tokens(&self, text: &str) -> Vec<String> {
-        match self {
-            Self::Spec(config, tokenizer) => match config.downcase_text {
-                true => Self::transform_filter(config, tokenizer.words(&text.to_lowercase())),
-                false => Self::transform_filter(config, tokenizer.words(text)),
+        self.tokens_ref(text).map(Cow::into_owned).collect()
+    }
+
+    // false when `text` ends inside a multi-line record (a quoted CSV field)
+    pub fn is_complete(&self, text: &str) -> bool {
+        let Self::Spec(_, tokenizer) = self;
+        tokenizer.is_complete(text)
+    }
+
+    // join raw input lines into records, a line ending inside a quoted CSV
+    // field continues on the next line (the "\n" is kept in the field)
+    //
+    // yields each record with the line number of its first line, other tokenizers
+    // yield each line, a quoted field open at the end of the input or longer than
+    // csv::MAX_RECORD is an ErrorKind::InvalidData error
+    pub fn records<'a, I>(&'a self, lines: I) -> impl Iterator<Item = io::Result<(usize, Vec<u8>)>> + 'a
+    where
+        I: IntoIterator<Item = io::Result<Vec<u8>>> + 'a,
+    {
+        let Self::Spec(_, tokenizer) = self;
+        let csv = match tokenizer {
+            WordTokenizer::Csv(csv) => Some(csv),
+            _ => None,
+        };
+        let mut lines = lines.into_iter();
+        let mut line_number = 0;
+        std::iter::from_fn(move || {
+            let mut record = match lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            line_number += 1;
+            let start = line_number;
+            let csv = match csv {
+                Some(csv) => csv,
+                None => return Some(Ok((start, record))),
+            };
+            let error = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, format!("line {start}: {reason}"));
+
+            let mut quoted = csv.ends_quoted(&record, false);
+            while quoted {
+                if record.len() > MAX_RECORD {
+                    return Some(Err(error(&format!("quoted field longer than {MAX_RECORD} bytes"))));
+                }
+                match lines.next() {
+                    Some(Ok(line)) => {
+                        line_number += 1;
+                        quoted = csv.ends_quoted(&line, true);
+                        record.push(b'\n');
+                        record.extend(line);
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => return Some(Err(error("unterminated quoted field"))),
+                }
+            }
+            Some(Ok((start, record)))
+        })
+    }
+
+    // tokens of raw input bytes
+    //
+    // the input is decoded lossily, a token which downcasing and the stages left
+    // unchanged is the original slice of `bytes` (invalid UTF-8 included), even when
+    // other tokens of the line changed, a changed token or a word n-gram is its UTF-8 text
+    pub fn tokens_bytes<'a>(&'a self, bytes: &'a [u8]) -> Vec<Cow<'a, [u8]>> {
+        let Self::Spec(config, _) = self;
+        let lossy = String::from_utf8_lossy(bytes);
+        let lossy_offsets = match &lossy {
+            Cow::Owned(lossy) => Some(OffsetMap::lossy(bytes, lossy)),
+            Cow::Borrowed(_) => None,
+        };
+        // the bytes of the range [start, end) of `lossy`
+        let original = move |start: usize, end: usize| -> &'a [u8] {
+            match &lossy_offsets {
+                Some(offsets) => {
+                    let (start, end) = offsets.range(start, end);
+                    &bytes[start..end]
+                }
+                None => &bytes[start..end],
+            }
+        };
+
+        if config.ngrams.is_some() {
+            return self
+                .tokens_ref(&lossy)
+                .map(|tok| match tok {
+                    Cow::Borrowed(s) => {
+                        let start = offset_in(&lossy, s);
+                        Cow::Borrowed(original(start, start + s.len()))
+                    }
+                    Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+                })
+                .collect();
+        }
+
+        let lowered = match config.downcase_text {
+            true => downcase(&lossy),
+            false => Cow::Borrowed(&*lossy),
+        };
+        let lowered_offsets = match &lowered {
+            Cow::Owned(lowered) => Some(OffsetMap::new(&lossy, lowered, |c| c.to_lowercase().count())),
+            Cow::Borrowed(_) => None,
+        };
+        self.pipeline(&lowered, false)
+            .map(|(tok, _)| match tok {
+                Cow::Borrowed(s) => {
+                    let start = offset_in(&lowered, s);
+                    let (start, end) = match &lowered_offsets {
+                        Some(offsets) => offsets.range(start, start + s.len()),
+                        None => (start, start + s.len()),
+                    };
+                    match &lossy[start..end] == s {
+                        true => Cow::Borrowed(original(start, end)),
+                        false => Cow::Owned(s.as_bytes().to_vec()),
+                    }
+                }
+                Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+            })
+            .collect()
+    }
+
+    // tokens of raw input bytes under an Encoding policy
+    pub fn tokens_encoded<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        encoding: Encoding,
+    ) -> Result<Vec<Cow<'a, [u8]>>, TokenizeError> {
+        Ok(match encoding {
+            Encoding::Strict => self.tokens_ref(std::str::from_utf8(bytes)?).map(cow_bytes).collect(),
+            Encoding::Lossy => match String::from_utf8_lossy(bytes) {
+                Cow::Borrowed(text) => self.tokens_ref(text).map(cow_bytes).collect(),
+                Cow::Owned(text) => self
+                    .tokens_ref(&text)
+                    .map(|tok| Cow::Owned(tok.into_owned().into_bytes()))
+                    .collect(),
             },
+            Encoding::Bytes => self.tokens_bytes(bytes),
+        })
+    }
+
+    // tokens with their byte and char positions in `text`
+    //
+    // offsets refer to `text` even when downcasing changes the byte length, e.g. 'İ' => "i̇"
+    // a token changed by a stage is located by the word it was produced from
+    // a word n-gram spans its first to last token
+    // RegexBoundary spans include the boundaries between tokens, see is_token()
+    pub fn spans(&self, text: &str) -> Vec<Token> {
+        let Self::Spec(config, tokenizer) = self;
+        let lowered = match config.downcase_text {
+            true => downcase(text),
+            false => Cow::Borrowed(text),
+        };
+        let offsets = match &lowered {
+            Cow::Owned(lowered) => Some(OffsetMap::new(text, lowered, |c| c.to_lowercase().count())),
+            Cow::Borrowed(_) => None,
+        };
+
+        let mut chars = CharCounter::new(text);
+        // n-grams combine tokens only
+        let spans = self
+            .pipeline(&lowered, config.ngrams.is_none())
+            .map(|(tok, anchor)| {
+                let start = offset_in(&lowered, anchor);
+                let (byte_start, byte_end) = match &offsets {
+                    Some(offsets) => offsets.range(start, start + anchor.len()),
+                    None => (start, start + anchor.len()),
+                };
+                Token {
+                    text: tok.into_owned(),
+                    kind: tokenizer.kind(anchor),
+                    byte_start,
+                    byte_end,
+                    char_start: chars.at(byte_start),
+                    char_end: chars.at(byte_end),
+                }
+            })
+            .collect();
+        match &config.ngrams {
+            Some(ngrams) => ngrams.apply_spans(spans),
+            None => spans,
+        }
+    }
+
+    // whether a span of spans() is a token of tokens(), a RegexBoundary boundary is not
+    pub fn is_token(&self, span: &Token) -> bool {
+        let Self::Spec(config, tokenizer) = self;
+        span.kind == TokenKind::Word || config.ngrams.is_some() || !matches!(tokenizer, WordTokenizer::RegexBoundary(_))
+    }
+}
+
+fn cow_bytes(tok: Cow<'_, str>) -> Cow<'_, [u8]> {
+    match tok {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+    }
+}
+
+// lowercase `text`, borrowing when no char changes
+pub(crate) fn downcase(text: &str) -> Cow<'_, str> {
+    let unchanged = |c: char| {
+        let mut lower = c.to_lowercase();
+        lower.len() == 1 && lower.next() == Some(c)
+    };
+    match text.chars().all(unchanged) {
+        true => Cow::Borrowed(text),
+        false => Cow::Owned(text.to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tokenizer_from_spec, NGrams, NormalizationForm, Stage, TokenKind, TokenizationSpec, TokenizerType, WordList,
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn downcase_borrows_unchanged_text() {
+        assert!(matches!(downcase("already lower, 123"), Cow::Borrowed(_)));
+        assert!(matches!(downcase("Not Lower"), Cow::Owned(_)));
+        assert_eq!(downcase("ǅemal İ"), "ǆemal i\u{307}");
+    }
+
+    #[test]
+    fn downcased_tokens_borrow_unchanged_text() {
+        let spec = TokenizationSpec {
+            downcase_text: true,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let text = "Hello world İstanbul ve";
+        let tokens = tokenizer.tokens_ref(text).collect::<Vec<_>>();
+        assert_eq!(tokens, vec!["hello", "world", "i\u{307}stanbul", "ve"]);
+        assert!(matches!(tokens[0], Cow::Owned(_)));
+        assert!(matches!(tokens[1], Cow::Borrowed("world")));
+        assert!(matches!(tokens[2], Cow::Owned(_)));
+        // after a token which changed length, "ve" is still located in `text`
+        match tokens[3] {
+            Cow::Borrowed(s) => assert_eq!(offset_in(text, s), text.len() - 2),
+            Cow::Owned(_) => panic!("an unchanged token is borrowed"),
+        }
+    }
+
+    #[test]
+    fn tokens_ref_matches_tokens() {
+        let text = " Don't forget the 🍺+🍕 party!  Ünïcödé, naïve ";
+        for tokenizer_type in [
+            TokenizerType::SplitStr,
+            TokenizerType::UnicodeSegment,
+            TokenizerType::UnicodeWord,
+            TokenizerType::Whitespace,
+            TokenizerType::RegexBoundary,
+            TokenizerType::RegexMatch,
+            TokenizerType::RegexSplit,
+            TokenizerType::Csv,
+            TokenizerType::Grapheme,
+        ] {
+            for (downcase_text, trimmed_tokens) in [(false, false), (true, false), (false, true), (true, true)] {
+                let spec = TokenizationSpec {
+                    tokenizer_type: tokenizer_type.clone(),
+                    tokenizer_init_param: Some(" ".into()),
+                    downcase_text,
+                    trimmed_tokens,
+                    filter_tokens_re: Some("^$".into()),
+                    ..Default::default()
+                };
+                let tokenizer = tokenizer_from_spec(&spec).unwrap();
+                let Tokenizer::Spec(_, word_tokenizer) = &tokenizer;
+
+                // the allocating recipe: downcase -> words -> trim -> filter
+                let lowered = text.to_lowercase();
+                let expected = match downcase_text {
+                    true => word_tokenizer.ref_words(&lowered),
+                    false => word_tokenizer.ref_words(text),
+                }
+                .map(|t| match trimmed_tokens {
+                    true => t.trim(),
+                    false => t,
+                })
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>();
+
+                let refs = tokenizer.tokens_ref(text).collect::<Vec<_>>();
+                assert_eq!(refs, expected, "{spec:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn spans_locate_tokens() {
+        let text = "Don't forget, the 🍺+🍕 party!";
+        for tokenizer_type in [
+            TokenizerType::SplitStr,
+            TokenizerType::UnicodeSegment,
+            TokenizerType::UnicodeWord,
+            TokenizerType::Whitespace,
+            TokenizerType::RegexBoundary,
+            TokenizerType::RegexMatch,
+            TokenizerType::RegexSplit,
+            TokenizerType::Csv,
+            TokenizerType::Grapheme,
+        ] {
+            let spec = TokenizationSpec {
+                tokenizer_type,
+                tokenizer_init_param: Some(" ".into()),
+                ..Default::default()
+            };
+            let tokenizer = tokenizer_from_spec(&spec).unwrap();
+            let spans = tokenizer.spans(text);
+            assert_eq!(
+                spans
+                    .iter()
+                    .filter(|t| tokenizer.is_token(t))
+                    .map(|t| t.text.as_str())
+                    .collect::<Vec<_>>(),
+                tokenizer.tokens(text),
+                "{spec:?}"
+            );
+            for t in spans {
+                assert_eq!(&text[t.byte_start..t.byte_end], t.text);
+                assert_eq!(
+                    text.chars()
+                        .skip(t.char_start)
+                        .take(t.char_end - t.char_start)
+                        .collect::<String>(),
+                    t.text
+                );
+            }
         }
     }
+
+    #[test]
+    fn spans_kind() {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::UnicodeSegment,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let kinds = tokenizer.spans("a, b").into_iter().map(|t| t.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Word,
+                TokenKind::Boundary,
+                TokenKind::Boundary,
+                TokenKind::Word
+            ]
+        );
+
+        // RegexBoundary boundaries are spans but not tokens
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::RegexBoundary,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let spans = tokenizer.spans("a, b");
+        assert_eq!(
+            spans.iter().map(|t| (t.text.as_str(), &t.kind)).collect::<Vec<_>>(),
+            vec![
+                ("a", &TokenKind::Word),
+                (", ", &TokenKind::Boundary),
+                ("b", &TokenKind::Word)
+            ]
+        );
+        assert_eq!(
+            spans.iter().map(|t| tokenizer.is_token(t)).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+    }
+
+    #[test]
+    fn spans_after_downcase() {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::RegexBoundary,
+            downcase_text: true,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+
+        // 'İ' (2 bytes) lowercases to "i\u{307}" (3 bytes)
+        let text = "İSTANBUL, İzmir ve Ankara";
+        let spans = tokenizer
+            .spans(text)
+            .into_iter()
+            .filter(|t| tokenizer.is_token(t))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans.iter().map(|t| t.text.as_str()).collect::<Vec<_>>(),
+            vec!["i\u{307}stanbul", "i\u{307}zmir", "ve", "ankara"]
+        );
+        assert_eq!(
+            spans
+                .iter()
+                .map(|t| &text[t.byte_start..t.byte_end])
+                .collect::<Vec<_>>(),
+            vec!["İSTANBUL", "İzmir", "ve", "Ankara"]
+        );
+        assert_eq!(
+            spans.iter().map(|t| (t.char_start, t.char_end)).collect::<Vec<_>>(),
+            vec![(0, 8), (10, 15), (16, 18), (19, 25)]
+        );
+    }
+
+    #[test]
+    fn stages_in_order() {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::Whitespace,
+            stages: vec![
+                Stage::Normalize(NormalizationForm::NFKC),
+                Stage::StripPunctuation,
+                Stage::FoldAccents,
+                Stage::CaseFold,
+                Stage::Length {
+                    min: Some(2),
+                    max: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let text = "«Naïve» ﬁne STRAẞE, a ...";
+        assert_eq!(tokenizer.tokens(text), vec!["naive", "fine", "strasse"]);
+
+        // stages which narrow a borrowed token narrow its span
+        let spans = tokenizer.spans(text);
+        assert_eq!(
+            spans
+                .iter()
+                .map(|t| &text[t.byte_start..t.byte_end])
+                .collect::<Vec<_>>(),
+            vec!["Naïve", "ﬁne", "STRAẞE"]
+        );
+    }
+
+    #[test]
+    fn stage_regex_error_field() {
+        let spec = TokenizationSpec {
+            stages: vec![Stage::Trim, Stage::Filter("[".into())],
+            ..Default::default()
+        };
+        assert!(matches!(
+            tokenizer_from_spec(&spec),
+            Err(crate::error::TokenizeError::InvalidRegexError { field, .. }) if field == "stages[1]"
+        ));
+    }
+
+    #[test]
+    fn stopwords_and_keepwords() {
+        let mut spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::UnicodeWord,
+            stopwords: Some(WordList::Builtin("english".into())),
+            ..Default::default()
+        };
+        let text = "The cat and THE hat";
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens(text), vec!["The", "cat", "THE", "hat"]);
+
+        spec.downcase_text = true;
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens(text), vec!["cat", "hat"]);
+
+        spec.keepwords = Some(WordList::Words(vec!["Hat".into(), "the".into()]));
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens(text), vec!["hat"]);
+    }
+
+    #[test]
+    fn tokens_ref_borrows() {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::SplitStr,
+            tokenizer_init_param: Some(",".into()),
+            downcase_text: true,
+            trimmed_tokens: true,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+
+        let tokens = tokenizer.tokens_ref("a , b,c ").collect::<Vec<_>>();
+        assert_eq!(tokens, vec!["a", "b", "c"]);
+        assert!(tokens.iter().all(|t| matches!(t, Cow::Borrowed(_))));
+
+        // only the token downcasing changed is owned
+        let tokens = tokenizer.tokens_ref("A , b,c ").collect::<Vec<_>>();
+        assert_eq!(tokens, vec!["a", "b", "c"]);
+        assert!(matches!(tokens[0], Cow::Owned(_)));
+        assert!(tokens[1..].iter().all(|t| matches!(t, Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn ngrams() {
+        let mut spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::UnicodeWord,
+            downcase_text: true,
+            stopwords: Some(WordList::Builtin("lucene".into())),
+            ngrams: Some("1-2".parse().unwrap()),
+            ..Default::default()
+        };
+        let text = "The Quick fox";
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens(text), vec!["quick", "fox", "quick fox"]);
+        let spans = tokenizer.spans(text);
+        assert_eq!(
+            spans
+                .iter()
+                .map(|t| &text[t.byte_start..t.byte_end])
+                .collect::<Vec<_>>(),
+            vec!["Quick", "fox", "Quick fox"]
+        );
+
+        spec.ngrams = Some(NGrams {
+            chars: true,
+            pad: Some('_'),
+            ..NGrams::from_str("2").unwrap()
+        });
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        assert_eq!(tokenizer.tokens("fox"), vec!["_f", "fo", "ox", "x_"]);
+
+        spec.ngrams = Some("2-1".parse().unwrap());
+        assert!(matches!(
+            tokenizer_from_spec(&spec),
+            Err(crate::error::TokenizeError::InvalidTokenizerError(_))
+        ));
+    }
+
+    #[test]
+    fn encodings() {
+        let spec = TokenizationSpec {
+            stages: vec![Stage::Filter("^b".into())],
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let input = b"caf\xe9 bad \xff\xfe x";
+
+        assert!(matches!(
+            tokenizer.tokens_encoded(input, Encoding::Strict),
+            Err(crate::error::TokenizeError::Utf8Error(_))
+        ));
+        assert_eq!(
+            tokenizer.tokens_encoded(input, Encoding::Lossy).unwrap(),
+            vec!["caf\u{fffd}".as_bytes(), "\u{fffd}\u{fffd}".as_bytes(), b"x"]
+        );
+        let tokens = tokenizer.tokens_encoded(input, Encoding::Bytes).unwrap();
+        assert_eq!(tokens, vec![&b"caf\xe9"[..], b"\xff\xfe", b"x"]);
+        assert!(tokens.iter().all(|t| matches!(t, Cow::Borrowed(_))));
+
+        // valid input is the same in every mode
+        for encoding in [Encoding::Strict, Encoding::Lossy, Encoding::Bytes] {
+            assert_eq!(
+                tokenizer.tokens_encoded("Ünï x".as_bytes(), encoding).unwrap(),
+                vec!["Ünï".as_bytes(), b"x"]
+            );
+        }
+    }
+
+    #[test]
+    fn bytes_keep_unchanged_tokens() {
+        let spec = TokenizationSpec {
+            downcase_text: true,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+
+        // downcasing changes "Caf\xe9", "bad\xff" is still the original bytes
+        let tokens = tokenizer.tokens_encoded(b"Caf\xe9 bad\xff", Encoding::Bytes).unwrap();
+        assert_eq!(tokens, vec!["caf\u{fffd}".as_bytes(), b"bad\xff"]);
+        assert!(matches!(tokens[0], Cow::Owned(_)));
+        assert!(matches!(tokens[1], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn csv_records() {
+        let spec = TokenizationSpec {
+            tokenizer_type: TokenizerType::Csv,
+            ..Default::default()
+        };
+        let tokenizer = tokenizer_from_spec(&spec).unwrap();
+        let lines = ["a,\"two", "lines\",b", "c,d"].map(|l| Ok(l.as_bytes().to_vec()));
+        let records = tokenizer.records(lines).collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(records, vec![(1, b"a,\"two\nlines\",b".to_vec()), (3, b"c,d".to_vec())]);
+
+        // an open quote at the end of the input is an error at the line of its record
+        let lines = ["a,b", "c,\"open", "d"].map(|l| Ok(l.as_bytes().to_vec()));
+        let mut records = tokenizer.records(lines);
+        assert_eq!(records.next().unwrap().unwrap(), (1, b"a,b".to_vec()));
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: unterminated quoted field");
+
+        // other tokenizers do not join lines
+        let whitespace = tokenizer_from_spec(&TokenizationSpec::default()).unwrap();
+        let lines = ["a,\"b", "c"].map(|l| Ok(l.as_bytes().to_vec()));
+        let records = whitespace.records(lines).collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(records, vec![(1, b"a,\"b".to_vec()), (2, b"c".to_vec())]);
+
+        // a quoted field is located by its text between the quotes
+        let text = "x,\"Smith, John\"";
+        let spans = tokenizer.spans(text);
+        assert_eq!(spans[1].text, "Smith, John");
+        assert_eq!(&text[spans[1].byte_start..spans[1].byte_end], "Smith, John");
+
+        // a field changed by unquoting is located by the field as written
+        let text = "5\"\" screen,\"say \"\"hi\"\"\"";
+        let spans = tokenizer.spans(text);
+        assert_eq!(spans[0].text, "5\"\" screen");
+        assert_eq!(&text[spans[0].byte_start..spans[0].byte_end], "5\"\" screen");
+        assert_eq!(spans[1].text, "say \"hi\"");
+        assert_eq!(&text[spans[1].byte_start..spans[1].byte_end], "\"say \"\"hi\"\"\"");
+    }
 }
@@ -0,0 +1,164 @@
+use crate::error::TokenizeError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+//================================================
+// A WordList supplies the stopwords or keepwords
+// of a TokenizationSpec
+//    {"Words": ["foo", "bar"]}  -- inline list
+//    {"Path": "stop.txt"}       -- one word per line, '#' comments
+//    {"Builtin": "english"}     -- a list shipped with the crate
+//================================================
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum WordList {
+    Words(Vec<String>),
+    Path(PathBuf),
+    Builtin(String),
+}
+
+impl WordList {
+    // load the words, lowercased when `downcase`
+    pub fn load(&self, field: &str, downcase: bool) -> Result<HashSet<String>, TokenizeError> {
+        let error = |reason: String| TokenizeError::InvalidWordListError {
+            field: field.into(),
+            reason,
+        };
+
+        let words: Vec<String> = match self {
+            Self::Words(words) => words.clone(),
+            Self::Path(path) => std::fs::read_to_string(path)
+                .map_err(|e| error(format!("{:?}: {e}", path.as_os_str())))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect(),
+            Self::Builtin(name) => builtin(name)
+                .ok_or_else(|| error(format!("unknown builtin {name:?}, use one of {BUILTIN_NAMES:?}")))?
+                .iter()
+                .map(|w| w.to_string())
+                .collect(),
+        };
+
+        Ok(match downcase {
+            true => words.into_iter().map(|w| w.to_lowercase()).collect(),
+            false => words.into_iter().collect(),
+        })
+    }
+}
+
+// command line form of a WordList:
+//    "english"   -- a builtin name
+//    "@stop.txt" -- a file
+//    "foo,bar"   -- comma separated words
+impl FromStr for WordList {
+    type Err = TokenizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.strip_prefix('@') {
+            Some(path) => Self::Path(path.into()),
+            None if builtin(s).is_some() => Self::Builtin(s.into()),
+            None => Self::Words(s.split(',').map(String::from).collect()),
+        })
+    }
+}
+
+//================================================
+//           Builtin English stopword sets
+//================================================
+pub const BUILTIN_NAMES: [&str; 2] = ["english", "lucene"];
+
+fn builtin(name: &str) -> Option<&'static [&'static str]> {
+    match name {
+        "english" => Some(&ENGLISH),
+        "lucene" => Some(&LUCENE),
+        _ => None,
+    }
+}
+
+// NLTK English stopwords
+#[rustfmt::skip]
+const ENGLISH: [&str; 179] = [
+    "i", "me", "my", "myself", "we", "our", "ours", "ourselves", "you", "you're", "you've", "you'll", "you'd",
+    "your", "yours", "yourself", "yourselves", "he", "him", "his", "himself", "she", "she's", "her", "hers",
+    "herself", "it", "it's", "its", "itself", "they", "them", "their", "theirs", "themselves", "what", "which",
+    "who", "whom", "this", "that", "that'll", "these", "those", "am", "is", "are", "was", "were", "be", "been",
+    "being", "have", "has", "had", "having", "do", "does", "did", "doing", "a", "an", "the", "and", "but", "if",
+    "or", "because", "as", "until", "while", "of", "at", "by", "for", "with", "about", "against", "between",
+    "into", "through", "during", "before", "after", "above", "below", "to", "from", "up", "down", "in", "out",
+    "on", "off", "over", "under", "again", "further", "then", "once", "here", "there", "when", "where", "why",
+    "how", "all", "any", "both", "each", "few", "more", "most", "other", "some", "such", "no", "nor", "not",
+    "only", "own", "same", "so", "than", "too", "very", "s", "t", "can", "will", "just", "don", "don't",
+    "should", "should've", "now", "d", "ll", "m", "o", "re", "ve", "y", "ain", "aren", "aren't", "couldn",
+    "couldn't", "didn", "didn't", "doesn", "doesn't", "hadn", "hadn't", "hasn", "hasn't", "haven", "haven't",
+    "isn", "isn't", "ma", "mightn", "mightn't", "mustn", "mustn't", "needn", "needn't", "shan", "shan't",
+    "shouldn", "shouldn't", "wasn", "wasn't", "weren", "weren't", "won", "won't", "wouldn", "wouldn't",
+];
+
+// Lucene EnglishAnalyzer default stopwords
+#[rustfmt::skip]
+const LUCENE: [&str; 33] = [
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it", "no", "not",
+    "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they", "this", "to", "was",
+    "will", "with",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_are_sets() {
+        for name in BUILTIN_NAMES {
+            let words = builtin(name).unwrap();
+            assert_eq!(words.iter().collect::<HashSet<_>>().len(), words.len(), "{name}");
+        }
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "english".parse::<WordList>().unwrap(),
+            WordList::Builtin("english".into())
+        );
+        assert_eq!(
+            "@stop.txt".parse::<WordList>().unwrap(),
+            WordList::Path("stop.txt".into())
+        );
+        assert_eq!(
+            "The,a".parse::<WordList>().unwrap(),
+            WordList::Words(vec!["The".into(), "a".into()])
+        );
+    }
+
+    #[test]
+    fn load() {
+        let words = WordList::Words(vec!["The".into(), "a".into()]);
+        assert!(words.load("stopwords", false).unwrap().contains("The"));
+        assert!(words.load("stopwords", true).unwrap().contains("the"));
+        assert_eq!(
+            WordList::Builtin("lucene".into())
+                .load("stopwords", true)
+                .unwrap()
+                .len(),
+            33
+        );
+
+        let path = std::env::temp_dir().join(format!("{}-tokenize_wordlist_test.txt", std::process::id()));
+        std::fs::write(&path, "# comment\nfoo\n\n  Bar \n").unwrap();
+        let words = WordList::Path(path.clone()).load("keepwords", true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(words, HashSet::from(["foo".to_string(), "bar".to_string()]));
+
+        assert!(matches!(
+            WordList::Builtin("klingon".into()).load("stopwords", false),
+            Err(TokenizeError::InvalidWordListError { .. })
+        ));
+        assert!(matches!(
+            WordList::Path("/no/such/file".into()).load("stopwords", false),
+            Err(TokenizeError::InvalidWordListError { .. })
+        ));
+    }
+}